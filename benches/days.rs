@@ -0,0 +1,68 @@
+use aoc_2023::day::{day17, Measure, Phase, DAYS};
+use criterion::measurement::WallTime;
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkGroup, Criterion};
+use std::fs;
+
+fn read_input(day: &str) -> Option<String> {
+    let input_filepath = format!("./input/{}.txt", day);
+    match fs::read_to_string(&input_filepath) {
+        Ok(input) => Some(input),
+        Err(err) => {
+            eprintln!(
+                "Skipping {}: unable to read {}: {}",
+                day, input_filepath, err
+            );
+            None
+        }
+    }
+}
+
+/// Benchmarks every phase of a day as one function of the day's group.
+struct Bench<'a> {
+    group: BenchmarkGroup<'a, WallTime>,
+}
+
+impl Measure for Bench<'_> {
+    fn measure(&mut self, phase: Phase, run: &mut dyn FnMut()) {
+        // The next phase needs a result even when criterion filters this one out.
+        run();
+        self.group
+            .bench_function(phase.to_string(), |b| b.iter(&mut *run));
+    }
+}
+
+pub fn criterion_benchmark(c: &mut Criterion) {
+    for day in &DAYS {
+        let name = format!("day{:0>2}", day.number);
+        if let Some(input) = read_input(&name) {
+            let mut bench = Bench {
+                group: c.benchmark_group(&name),
+            };
+            (day.phases)(&input, &mut bench);
+            bench.group.finish();
+        }
+    }
+}
+
+/// The bucket queue search of day 17 against the A* search it replaced, which rendering still uses.
+pub fn day17_search(c: &mut Criterion) {
+    let Some(input) = read_input("day17") else {
+        return;
+    };
+    let grid = day17::prepare(&input);
+    let mut group = c.benchmark_group("day17_search");
+    for (part, range) in [("part1", 1..=3), ("part2", 4..=10)] {
+        group.bench_function(format!("{}_buckets", part), |b| {
+            b.iter(|| day17::solve_part(black_box(&grid), range.clone()))
+        });
+        group.bench_function(format!("{}_a_star", part), |b| {
+            b.iter(|| {
+                day17::find_path(black_box(&grid), range.clone()).map(|(heat_loss, _)| heat_loss)
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark, day17_search);
+criterion_main!(benches);
@@ -0,0 +1,102 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::fmt::{Display, Formatter};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+static LIVE_BYTES: AtomicUsize = AtomicUsize::new(0);
+static PEAK_LIVE_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+/// Forwards to the system allocator while keeping track of the number and size of allocations.
+pub struct CountingAllocator;
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+fn record_allocation(allocated: usize, freed: usize) {
+    ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    ALLOCATED_BYTES.fetch_add(allocated, Ordering::Relaxed);
+    let live = if allocated >= freed {
+        LIVE_BYTES.fetch_add(allocated - freed, Ordering::Relaxed) + (allocated - freed)
+    } else {
+        LIVE_BYTES.fetch_sub(freed - allocated, Ordering::Relaxed) - (freed - allocated)
+    };
+    PEAK_LIVE_BYTES.fetch_max(live, Ordering::Relaxed);
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            record_allocation(layout.size(), 0);
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            record_allocation(layout.size(), 0);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        LIVE_BYTES.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            // Counted as a fresh allocation of the new size which replaces the old one.
+            record_allocation(new_size, layout.size());
+        }
+        new_ptr
+    }
+}
+
+pub struct Allocations {
+    pub count: usize,
+    pub bytes: usize,
+    /// Highest number of bytes alive at once, relative to the start of the measurement.
+    pub peak_bytes: usize,
+}
+
+impl Display for Allocations {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} allocations, {} bytes, {} peak bytes",
+            self.count, self.bytes, self.peak_bytes
+        )
+    }
+}
+
+pub struct Measurement {
+    count: usize,
+    bytes: usize,
+    live_bytes: usize,
+}
+
+pub fn start() -> Measurement {
+    let live_bytes = LIVE_BYTES.load(Ordering::Relaxed);
+    PEAK_LIVE_BYTES.store(live_bytes, Ordering::Relaxed);
+    Measurement {
+        count: ALLOCATIONS.load(Ordering::Relaxed),
+        bytes: ALLOCATED_BYTES.load(Ordering::Relaxed),
+        live_bytes,
+    }
+}
+
+impl Measurement {
+    pub fn finish(self) -> Allocations {
+        Allocations {
+            count: ALLOCATIONS.load(Ordering::Relaxed) - self.count,
+            bytes: ALLOCATED_BYTES.load(Ordering::Relaxed) - self.bytes,
+            peak_bytes: PEAK_LIVE_BYTES
+                .load(Ordering::Relaxed)
+                .saturating_sub(self.live_bytes),
+        }
+    }
+}
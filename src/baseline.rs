@@ -0,0 +1,82 @@
+use aoc_2023::day::Phase;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+/// Nanoseconds per phase, per day.
+pub type Timings = BTreeMap<String, BTreeMap<String, u64>>;
+pub type Baselines = BTreeMap<String, Timings>;
+
+pub fn insert(timings: &mut Timings, day: usize, phase: Phase, duration: Duration) {
+    timings
+        .entry(format!("day{:0>2}", day))
+        .or_default()
+        .insert(phase.to_string(), duration.as_nanos() as u64);
+}
+
+/// Reads the baselines saved in the file, none if it does not exist yet.
+pub fn load_all(path: &Path) -> Result<Baselines, String> {
+    match fs::read_to_string(path) {
+        Ok(contents) => serde_json::from_str(&contents)
+            .map_err(|err| format!("unable to parse {}: {}", path.display(), err)),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Baselines::default()),
+        Err(err) => Err(format!("unable to read {}: {}", path.display(), err)),
+    }
+}
+
+/// Stores the timings under the given name. Days that were not run keep their previous timings.
+pub fn save(
+    path: &Path,
+    mut baselines: Baselines,
+    name: &str,
+    timings: &Timings,
+) -> Result<(), String> {
+    let baseline = baselines.entry(name.to_string()).or_default();
+    for (day, phases) in timings {
+        baseline.insert(day.clone(), phases.clone());
+    }
+    let contents = serde_json::to_string_pretty(&baselines).map_err(|err| err.to_string())?;
+    if let Some(directory) = path.parent() {
+        fs::create_dir_all(directory)
+            .map_err(|err| format!("unable to create {}: {}", directory.display(), err))?;
+    }
+    fs::write(path, contents).map_err(|err| format!("unable to write {}: {}", path.display(), err))
+}
+
+/// Prints the relative change of every phase present in both timings, returns the number of regressions.
+pub fn compare(baseline: &Timings, current: &Timings, threshold_percent: f64) -> usize {
+    println!(
+        "{:<6} {:<8} {:>12} {:>12} {:>9}",
+        "day", "phase", "baseline", "current", "change"
+    );
+    let mut regressions = 0;
+    for (day, phases) in current {
+        for phase in Phase::ALL {
+            let phase = phase.to_string();
+            let Some(&nanos) = phases.get(&phase) else {
+                continue;
+            };
+            let Some(&baseline_nanos) = baseline.get(day).and_then(|phases| phases.get(&phase))
+            else {
+                continue;
+            };
+            let change = (nanos as f64 / baseline_nanos.max(1) as f64 - 1.0) * 100.0;
+            let is_regression = change > threshold_percent;
+            if is_regression {
+                regressions += 1;
+            }
+            println!(
+                "{:<6} {:<8} {:>12} {:>12} {:>+8.1}%{}",
+                day,
+                phase,
+                format!("{:.2?}", Duration::from_nanos(baseline_nanos)),
+                format!("{:.2?}", Duration::from_nanos(nanos)),
+                change,
+                if is_regression { "  REGRESSION" } else { "" }
+            );
+        }
+    }
+    regressions
+}
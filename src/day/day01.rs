@@ -1,3 +1,4 @@
+use crate::error::Error;
 use crate::solution::Solution;
 use bstr::ByteSlice;
 
@@ -7,6 +8,14 @@ pub fn prepare(input: &str) -> PreparedInput<'_> {
     input.as_bytes().lines().collect()
 }
 
+pub fn try_prepare(input: &str) -> Result<PreparedInput<'_>, Error> {
+    let input = prepare(input);
+    if !input.iter().all(|line| line.iter().any(u8::is_ascii_digit)) {
+        return Err(Error::Invalid("every line needs a digit"));
+    }
+    Ok(input)
+}
+
 pub fn solve_part1(input: &PreparedInput) -> u32 {
     input
         .iter()
@@ -90,14 +99,19 @@ pub fn solve_part2(input: &PreparedInput) -> u32 {
         .sum()
 }
 
+pub fn try_solve(input: &str) -> Result<(Solution, Solution), Error> {
+    let input = try_prepare(input)?;
+    Ok((solve_part1(&input).into(), solve_part2(&input).into()))
+}
+
 pub fn solve(input: &str) -> (Solution, Solution) {
-    let input = prepare(input);
-    (solve_part1(&input).into(), solve_part2(&input).into())
+    try_solve(input).unwrap()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::generate::{self, assert_matches_reference};
 
     const EXAMPLE_INPUT: &str = "1abc2
 pqr3stu8vwx
@@ -126,4 +140,41 @@ zoneight234
             281
         );
     }
+
+    fn reference(input: &str, words: bool) -> u32 {
+        const WORDS: [&str; 9] = [
+            "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+        ];
+        input
+            .lines()
+            .map(|line| {
+                let digits = (0..line.len())
+                    .filter_map(|i| {
+                        let rest = &line[i..];
+                        let digit = rest.chars().next().unwrap().to_digit(10);
+                        digit.or_else(|| {
+                            (1..=9)
+                                .zip(WORDS)
+                                .find(|&(_, word)| words && rest.starts_with(word))
+                                .map(|(value, _)| value)
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                digits[0] * 10 + digits[digits.len() - 1]
+            })
+            .sum()
+    }
+    #[test]
+    fn matches_reference() {
+        assert_matches_reference(
+            generate::day01::generate,
+            20,
+            &[0.1],
+            |input| {
+                let prepared = prepare(input);
+                (solve_part1(&prepared), solve_part2(&prepared))
+            },
+            |input| (reference(input, false), reference(input, true)),
+        );
+    }
 }
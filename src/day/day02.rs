@@ -1,3 +1,4 @@
+use crate::error::Error;
 use crate::solution::Solution;
 use bstr::ByteSlice;
 use winnow::ascii::dec_uint;
@@ -27,10 +28,11 @@ impl Accumulate<(u8, Color)> for Draw {
     }
 
     fn accumulate(&mut self, acc: (u8, Color)) {
+        // Colors are not repeated within a draw in valid inputs, saturating keeps others from overflowing.
         match acc.1 {
-            Color::Red => self.red += acc.0,
-            Color::Green => self.green += acc.0,
-            Color::Blue => self.blue += acc.0,
+            Color::Red => self.red = self.red.saturating_add(acc.0),
+            Color::Green => self.green = self.green.saturating_add(acc.0),
+            Color::Blue => self.blue = self.blue.saturating_add(acc.0),
         }
     }
 }
@@ -72,36 +74,48 @@ fn parse_game(input: &mut &[u8]) -> PResult<Game> {
     Ok(Game { id, largest_draw })
 }
 
+pub fn try_prepare(input: &str) -> impl Iterator<Item = Result<Game, Error>> + '_ {
+    input.as_bytes().lines().enumerate().map(|(i, line)| {
+        parse_game.parse(line).map_err(|err| Error::Parse {
+            line: i + 1,
+            column: err.offset() + 1,
+        })
+    })
+}
+
 pub fn prepare(input: &str) -> impl Iterator<Item = Game> + '_ {
-    input
-        .as_bytes()
-        .lines()
-        .map(|line| parse_game.parse(line).unwrap())
+    try_prepare(input).map(Result::unwrap)
 }
 
-pub fn solve(input: &str) -> (Solution, Solution) {
-    let games = prepare(input);
+pub fn try_solve(input: &str) -> Result<(Solution, Solution), Error> {
+    let mut games = try_prepare(input);
 
-    let (part1, part2) = games.fold((0u32, 0u32), |(mut part1, mut part2), game| {
+    let (part1, part2) = games.try_fold((0u32, 0u64), |(mut part1, mut part2), game| {
+        let game = game?;
         if game.largest_draw.red <= 12
             && game.largest_draw.green <= 13
             && game.largest_draw.blue <= 14
         {
             part1 += game.id as u32;
         }
-        part2 += game.largest_draw.red as u32
-            * game.largest_draw.green as u32
-            * game.largest_draw.blue as u32;
+        part2 += game.largest_draw.red as u64
+            * game.largest_draw.green as u64
+            * game.largest_draw.blue as u64;
 
-        (part1, part2)
-    });
+        Ok::<_, Error>((part1, part2))
+    })?;
 
-    (part1.into(), part2.into())
+    Ok((part1.into(), part2.into()))
+}
+
+pub fn solve(input: &str) -> (Solution, Solution) {
+    try_solve(input).unwrap()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::generate::{self, assert_matches_reference};
 
     const EXAMPLE_INPUT: &str = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
 Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
@@ -118,6 +132,36 @@ Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
     }
     #[test]
     fn example_part2() {
-        assert_eq!(solve(EXAMPLE_INPUT).1, 2286u32.into());
+        assert_eq!(solve(EXAMPLE_INPUT).1, 2286u64.into());
+    }
+
+    fn reference(input: &str) -> (u32, u64) {
+        input.lines().fold((0, 0), |(part1, part2), line| {
+            let (game, draws) = line.split_once(": ").unwrap();
+            let id: u32 = game["Game ".len()..].parse().unwrap();
+            let (mut red, mut green, mut blue) = (0, 0, 0);
+            for cubes in draws.split("; ").flat_map(|draw| draw.split(", ")) {
+                let (count, color) = cubes.split_once(' ').unwrap();
+                let count: u64 = count.parse().unwrap();
+                match color {
+                    "red" => red = red.max(count),
+                    "green" => green = green.max(count),
+                    "blue" => blue = blue.max(count),
+                    _ => unreachable!(),
+                }
+            }
+            let possible = red <= 12 && green <= 13 && blue <= 14;
+            (
+                part1 + if possible { id } else { 0 },
+                part2 + red * green * blue,
+            )
+        })
+    }
+    #[test]
+    fn matches_reference() {
+        assert_matches_reference(generate::day02::generate, 20, &[0.1], solve, |input| {
+            let (part1, part2) = reference(input);
+            (part1.into(), part2.into())
+        });
     }
 }
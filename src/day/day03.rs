@@ -1,5 +1,8 @@
+use crate::error::Error;
 use crate::solution::Solution;
-use crate::util::grid::Grid;
+use crate::util::grid::{check_rows, BackedGrid, Grid, GridView};
+use crate::util::position::Position;
+use crate::util::render::{Color, Renderer};
 use bstr::ByteSlice;
 use std::ops::Range;
 use winnow::ascii::dec_uint;
@@ -17,11 +20,14 @@ pub enum Cell {
 
 type PreparedInput = (Vec<Vec<(u32, Range<usize>)>>, Grid<Cell>);
 
-pub fn prepare(input: &str) -> PreparedInput {
+pub fn try_prepare(input: &str) -> Result<PreparedInput, Error> {
+    // Any other character is a symbol, carriage returns would be stripped as part of the line ending.
+    check_rows(input.as_bytes(), |c| c != b'\r')?;
     let numbers = input
         .as_bytes()
         .lines()
-        .map(|line| {
+        .enumerate()
+        .map(|(i, line)| {
             delimited(
                 winnow::token::take_till0('0'..='9'),
                 separated(
@@ -32,9 +38,16 @@ pub fn prepare(input: &str) -> PreparedInput {
                 winnow::token::take_till0('0'..='9'),
             )
             .parse(Located::new(line))
-            .unwrap()
+            .map_err(|err| Error::Parse {
+                line: i + 1,
+                column: err.offset() + 1,
+            })
         })
-        .collect::<Vec<_>>();
+        .collect::<Result<Vec<Vec<(u32, Range<usize>)>>, _>>()?;
+    // Longer numbers could overflow the sum of the gear ratios.
+    if numbers.iter().flatten().any(|(_, range)| range.len() > 3) {
+        return Err(Error::Invalid("part numbers have at most three digits"));
+    }
 
     let grid = Grid::from_rows(input.as_bytes().lines().map(|line| {
         line.iter().map(|c| match c {
@@ -44,7 +57,11 @@ pub fn prepare(input: &str) -> PreparedInput {
             _ => Cell::Symbol,
         })
     }));
-    (numbers, grid)
+    Ok((numbers, grid))
+}
+
+pub fn prepare(input: &str) -> PreparedInput {
+    try_prepare(input).unwrap()
 }
 
 pub fn solve_part1(input: &PreparedInput) -> u32 {
@@ -52,62 +69,96 @@ pub fn solve_part1(input: &PreparedInput) -> u32 {
     numbers
         .iter()
         .enumerate()
-        .map(|(j, row)| {
-            let j_range = j.saturating_sub(1)..=(j + 1).min(numbers.len() - 1);
-            row.iter()
-                .map(|(num, range)| {
-                    let i_range =
-                        range.start.saturating_sub(1)..=range.end.min(grid.dimensions.1 - 1);
-                    for j in j_range.clone() {
-                        for i in i_range.clone() {
-                            if matches!(grid[j][i], Cell::Symbol | Cell::Gear) {
-                                return *num;
-                            }
-                        }
-                    }
-                    0u32
-                })
-                .sum::<u32>()
-        })
+        .flat_map(|(j, row)| row.iter().map(move |(num, range)| (j, num, range)))
+        .filter(|&(j, _, range)| is_part_number(grid, j, range))
+        .map(|(_, num, _)| num)
         .sum()
 }
 
-pub fn solve_part2(input: &PreparedInput) -> u32 {
+/// Looks for a symbol in the box around the number, which spans `range` in row `j`.
+fn is_part_number(grid: &Grid<Cell>, j: usize, range: &Range<usize>) -> bool {
+    let start = Position(j, range.start);
+    let length = range.len() as isize;
+    // Offsets from the first digit, the cells outside the grid are skipped.
+    (-1..=1).any(|dy| {
+        (-1..=length).any(|dx| {
+            matches!(
+                grid.get_offset(&start, dy, dx),
+                Some(Cell::Symbol | Cell::Gear)
+            )
+        })
+    })
+}
+
+/// Draws the schematic with the part numbers, the other numbers and the symbols highlighted.
+pub fn render(input: &str) -> Result<Renderer, Error> {
+    let (numbers, grid) = try_prepare(input)?;
+    let (mut parts, mut others) = (vec![], vec![]);
+    for (j, row) in numbers.iter().enumerate() {
+        for (_, range) in row {
+            let positions = match is_part_number(&grid, j, range) {
+                true => &mut parts,
+                false => &mut others,
+            };
+            positions.extend(range.clone().map(|i| Position(j, i)));
+        }
+    }
+    let symbols = grid
+        .iter()
+        .filter(|(_, cell)| matches!(cell, Cell::Symbol | Cell::Gear))
+        .map(|(pos, _)| pos);
+    let text = BackedGrid::from_data_and_row_separator(input.as_bytes(), b'\n');
+    Ok(Renderer::new(&text, |&c| c as char)
+        .overlay(Color::Yellow, symbols)
+        .overlay(Color::Green, parts)
+        .overlay(Color::Red, others))
+}
+
+/// The product of the two numbers around the gear at `pos`, 0 if there are not exactly two.
+// Inlined, it makes the scan for gears over the whole grid twice as slow.
+#[inline(never)]
+fn gear_ratio(numbers: &[Vec<(u32, Range<usize>)>], grid: &Grid<Cell>, pos: &Position) -> u64 {
+    let mut matching_numbers_mult = 1u64;
+    let mut matching_numbers_count = 0u8;
+    // The rows above, at and below the gear that are inside the grid.
+    for row in (-1..=1).filter_map(|dy| grid.offset_position(pos, dy, 0)) {
+        for (num, range) in &numbers[row.y()] {
+            // The last digit is at most one column left of the gear, the first at most one right.
+            if range.start <= pos.x() + 1 && range.end >= pos.x() {
+                matching_numbers_mult = matching_numbers_mult.wrapping_mul(*num as u64);
+                matching_numbers_count += 1;
+            }
+        }
+    }
+    if matching_numbers_count != 2 {
+        return 0;
+    }
+    matching_numbers_mult
+}
+
+pub fn solve_part2(input: &PreparedInput) -> u64 {
     let (numbers, grid) = input;
     grid.iter()
         .filter(|(_, cell)| matches!(cell, Cell::Gear))
-        .map(|(pos, _)| {
-            let j_range = pos.y().saturating_sub(1)..=(pos.y() + 1).min(grid.dimensions.0 - 1);
-            // No fix is necessary on the bound in the x-direction because it is not used in indices.
-            let i_range = pos.x().saturating_sub(1)..=(pos.x() + 1);
-
-            let mut matching_numbers_mult = 1u32;
-            let mut matching_numbers_count = 0u8;
-            for j in j_range {
-                let row_numbers = &numbers[j];
-                for (num, range) in row_numbers {
-                    if range.start <= *i_range.end() && range.end > *i_range.start() {
-                        matching_numbers_mult = matching_numbers_mult.wrapping_mul(*num);
-                        matching_numbers_count += 1;
-                    }
-                }
-            }
-            if matching_numbers_count != 2 {
-                return 0;
-            }
-            matching_numbers_mult
-        })
+        .map(|(pos, _)| gear_ratio(numbers, grid, &pos))
         .sum()
 }
 
+pub fn try_solve(input: &str) -> Result<(Solution, Solution), Error> {
+    let input = try_prepare(input)?;
+    Ok((solve_part1(&input).into(), solve_part2(&input).into()))
+}
+
 pub fn solve(input: &str) -> (Solution, Solution) {
-    let input = prepare(input);
-    (solve_part1(&input).into(), solve_part2(&input).into())
+    try_solve(input).unwrap()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::generate::{self, assert_matches_reference};
+    use crate::util::position::Dimensions;
+    use std::ops::Range;
 
     const EXAMPLE_INPUT: &str = "467..114..
 ...*......
@@ -126,14 +177,91 @@ mod tests {
         assert_eq!(numbers[1], vec![]);
         assert_eq!(numbers[2], vec![(35, 2..4), (633, 6..9)]);
 
-        assert_eq!(grid.dimensions, (10, 10));
+        assert_eq!(grid.dimensions, Dimensions::from_height_width(10, 10));
     }
     #[test]
     fn example_part1() {
         assert_eq!(solve_part1(&prepare(EXAMPLE_INPUT)), 4361);
     }
     #[test]
+    fn example_render() {
+        let text = render(EXAMPLE_INPUT).unwrap().with_color(true).to_string();
+        let lines = text.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], "\x1b[32m467\x1b[0m..\x1b[31m114\x1b[0m..");
+        assert_eq!(lines[3], "......\x1b[33m#\x1b[0m...");
+        let plain = render(EXAMPLE_INPUT).unwrap().with_color(false).to_string();
+        assert_eq!(plain, EXAMPLE_INPUT.to_string() + "\n");
+    }
+    #[test]
     fn example_part2() {
         assert_eq!(solve_part2(&prepare(EXAMPLE_INPUT)), 467835);
     }
+
+    fn reference(input: &str) -> (u32, u64) {
+        let grid = input.lines().map(str::as_bytes).collect::<Vec<_>>();
+        // Every number with its row and column span.
+        let mut numbers = vec![];
+        for (y, row) in grid.iter().enumerate() {
+            let mut x = 0;
+            while x < row.len() {
+                let start = x;
+                while x < row.len() && row[x].is_ascii_digit() {
+                    x += 1;
+                }
+                if x > start {
+                    let value: u32 = std::str::from_utf8(&row[start..x])
+                        .unwrap()
+                        .parse()
+                        .unwrap();
+                    numbers.push((value, y, start..x));
+                } else {
+                    x += 1;
+                }
+            }
+        }
+        let adjacent = |y: usize, columns: &Range<usize>, cy: usize, cx: usize| {
+            cy + 1 >= y && cy <= y + 1 && cx + 1 >= columns.start && cx <= columns.end
+        };
+
+        let mut part1 = 0;
+        let mut part2 = 0;
+        for (cy, row) in grid.iter().enumerate() {
+            for (cx, &c) in row.iter().enumerate() {
+                if c == b'.' || c.is_ascii_digit() {
+                    continue;
+                }
+                let neighbours = numbers
+                    .iter()
+                    .filter(|(_, y, columns)| adjacent(*y, columns, cy, cx))
+                    .collect::<Vec<_>>();
+                if c == b'*' && neighbours.len() == 2 {
+                    part2 += neighbours[0].0 as u64 * neighbours[1].0 as u64;
+                }
+            }
+        }
+        for (value, y, columns) in &numbers {
+            let has_symbol = grid.iter().enumerate().any(|(cy, row)| {
+                row.iter().enumerate().any(|(cx, &c)| {
+                    c != b'.' && !c.is_ascii_digit() && adjacent(*y, columns, cy, cx)
+                })
+            });
+            if has_symbol {
+                part1 += value;
+            }
+        }
+        (part1, part2)
+    }
+    #[test]
+    fn matches_reference() {
+        assert_matches_reference(
+            generate::day03::generate,
+            20,
+            &[0.05],
+            |input| {
+                let prepared = prepare(input);
+                (solve_part1(&prepared), solve_part2(&prepared))
+            },
+            reference,
+        );
+    }
 }
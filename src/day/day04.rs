@@ -1,3 +1,4 @@
+use crate::error::Error;
 use crate::solution::Solution;
 use bstr::ByteSlice;
 use rustc_hash::FxHashSet;
@@ -37,38 +38,55 @@ fn parse_card<'a>() -> impl Parser<&'a [u8], usize, ContextError> {
     }
 }
 
-pub fn prepare(input: &str) -> impl Iterator<Item = usize> + '_ {
+pub fn try_prepare(input: &str) -> impl Iterator<Item = Result<usize, Error>> + '_ {
     let mut parse_card = parse_card();
-    input
-        .as_bytes()
-        .lines()
-        .map(move |line| parse_card.parse(line).unwrap())
+    input.as_bytes().lines().enumerate().map(move |(i, line)| {
+        parse_card.parse(line).map_err(|err| Error::Parse {
+            line: i + 1,
+            column: err.offset() + 1,
+        })
+    })
 }
 
-pub fn solve(input: &str) -> (Solution, Solution) {
-    let input = prepare(input);
-
-    let mut copies = vec![];
+pub fn prepare(input: &str) -> impl Iterator<Item = usize> + '_ {
+    try_prepare(input).map(Result::unwrap)
+}
 
-    let mut part1 = 0;
-    input.enumerate().for_each(|(i, count)| {
+pub fn try_solve(input: &str) -> Result<(Solution, Solution), Error> {
+    const OVERFLOW: Error = Error::Invalid("too many points or copies");
+    let input = try_prepare(input);
+    let mut copies: Vec<u32> = vec![];
+    let mut part1 = 0u32;
+    for (i, count) in input.enumerate() {
+        let count = count?;
         if count > 0 {
-            part1 += 2u32.pow(count as u32 - 1);
+            part1 = 1u32
+                .checked_shl(count as u32 - 1)
+                .and_then(|points| part1.checked_add(points))
+                .ok_or(OVERFLOW)?;
         }
         let copies_len = copies.len();
         copies.resize(copies_len.max(i + count + 1), 1);
         for next in (i + 1)..(i + 1 + count) {
-            copies[next] += copies[i];
+            copies[next] = copies[next].checked_add(copies[i]).ok_or(OVERFLOW)?;
         }
-    });
-    let part2: u32 = copies.iter().sum();
+    }
+    let part2 = copies
+        .iter()
+        .try_fold(0u32, |sum, copies| sum.checked_add(*copies))
+        .ok_or(OVERFLOW)?;
+
+    Ok((part1.into(), part2.into()))
+}
 
-    (part1.into(), part2.into())
+pub fn solve(input: &str) -> (Solution, Solution) {
+    try_solve(input).unwrap()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::generate::{self, assert_matches_reference};
 
     const EXAMPLE_INPUT: &str = "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
 Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
@@ -88,4 +106,39 @@ Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11";
     fn example_part2() {
         assert_eq!(solve(EXAMPLE_INPUT).1, 30u32.into());
     }
+
+    fn reference(input: &str) -> (u32, u32) {
+        let matches = input
+            .lines()
+            .map(|line| {
+                let (winning, numbers) =
+                    line.split_once(": ").unwrap().1.split_once(" | ").unwrap();
+                let winning = winning.split_whitespace().collect::<Vec<_>>();
+                numbers
+                    .split_whitespace()
+                    .filter(|num| winning.contains(num))
+                    .count()
+            })
+            .collect::<Vec<_>>();
+        let part1 = matches
+            .iter()
+            .map(|&count| if count > 0 { 1 << (count - 1) } else { 0 })
+            .sum();
+
+        // Scratches every single card, including each copy, one at a time.
+        let mut part2 = 0;
+        let mut pile = (0..matches.len()).collect::<Vec<_>>();
+        while let Some(card) = pile.pop() {
+            part2 += 1;
+            pile.extend(card + 1..card + 1 + matches[card]);
+        }
+        (part1, part2)
+    }
+    #[test]
+    fn matches_reference() {
+        assert_matches_reference(generate::day04::generate, 20, &[0.1], solve, |input| {
+            let (part1, part2) = reference(input);
+            (part1.into(), part2.into())
+        });
+    }
 }
@@ -1,3 +1,4 @@
+use crate::error::Error;
 use crate::solution::Solution;
 use std::ops::Range;
 use winnow::ascii::dec_uint;
@@ -15,8 +16,8 @@ pub enum Cell {
 
 type PreparedInput = (Vec<u64>, Vec<Vec<(u64, u64, u64)>>);
 
-pub fn prepare(input: &str) -> PreparedInput {
-    separated_pair(
+pub fn try_prepare(input: &str) -> Result<PreparedInput, Error> {
+    let (seeds, mappings): PreparedInput = separated_pair(
         preceded("seeds: ", separated(1.., dec_uint::<_, u64, ()>, ' ')),
         "\n\n",
         separated(
@@ -44,7 +45,28 @@ pub fn prepare(input: &str) -> PreparedInput {
         ),
     )
     .parse(input)
-    .unwrap()
+    .map_err(|err| Error::parse_at(input.as_bytes(), err.offset()))?;
+
+    if seeds.len() % 2 != 0 || seeds.chunks_exact(2).any(|chunk| chunk[1] == 0) {
+        return Err(Error::Invalid("seeds have to form non-empty ranges"));
+    }
+    let overflows = seeds
+        .chunks_exact(2)
+        .any(|chunk| chunk[0].checked_add(chunk[1]).is_none())
+        || mappings
+            .iter()
+            .flatten()
+            .any(|&(output_start, input_start, length)| {
+                output_start.max(input_start).checked_add(length).is_none()
+            });
+    if overflows {
+        return Err(Error::Invalid("ranges have to end below u64::MAX"));
+    }
+    Ok((seeds, mappings))
+}
+
+pub fn prepare(input: &str) -> PreparedInput {
+    try_prepare(input).unwrap()
 }
 
 pub fn solve_part1(input: &PreparedInput) -> u64 {
@@ -56,7 +78,7 @@ pub fn solve_part1(input: &PreparedInput) -> u64 {
                 mapping
                     .iter()
                     .find(|&&(_, input_start, length)| {
-                        previous >= input_start && previous <= input_start + length
+                        previous >= input_start && previous < input_start + length
                     })
                     .map_or(previous, |map| previous - map.1 + map.0)
             })
@@ -69,11 +91,21 @@ fn range_intersect<T>(a: &Range<T>, b: &Range<T>) -> Option<Range<T>>
 where
     T: PartialOrd + Ord + Copy,
 {
-    if a.end < b.start || a.start > b.end {
-        None
-    } else {
-        Some(a.start.max(b.start)..a.end.min(b.end))
+    let intersection = a.start.max(b.start)..a.end.min(b.end);
+    (intersection.start < intersection.end).then_some(intersection)
+}
+
+/// Sorts the ranges and joins the ones which overlap or touch.
+fn merge_ranges(mut ranges: Vec<Range<u64>>) -> Vec<Range<u64>> {
+    ranges.sort_unstable_by_key(|range| range.start);
+    let mut merged: Vec<Range<u64>> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+            _ => merged.push(range),
+        }
     }
+    merged
 }
 
 pub fn solve_part2(input: &PreparedInput) -> u64 {
@@ -84,13 +116,16 @@ pub fn solve_part2(input: &PreparedInput) -> u64 {
             .map(|chunk| chunk[0]..chunk[0] + chunk[1])
             .collect::<Vec<_>>(),
         |numbers, mapping| {
+            // Different ranges can map onto overlapping ranges, merging keeps their number from
+            // growing with every mapping.
+            let numbers = merge_ranges(numbers);
             numbers
                 .into_iter()
                 .flat_map(|mut previous| {
                     let mut result = vec![];
                     for &(output_start, input_start, length) in mapping {
                         // As the number ranges are sorted, if we are fully beyond we can break the loop
-                        if previous.end < input_start {
+                        if previous.end <= input_start {
                             break;
                         }
                         match range_intersect(&previous, &(input_start..input_start + length)) {
@@ -118,14 +153,19 @@ pub fn solve_part2(input: &PreparedInput) -> u64 {
     numbers.into_iter().map(|range| range.start).min().unwrap()
 }
 
+pub fn try_solve(input: &str) -> Result<(Solution, Solution), Error> {
+    let input = try_prepare(input)?;
+    Ok((solve_part1(&input).into(), solve_part2(&input).into()))
+}
+
 pub fn solve(input: &str) -> (Solution, Solution) {
-    let input = prepare(input);
-    (solve_part1(&input).into(), solve_part2(&input).into())
+    try_solve(input).unwrap()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::generate::{self, assert_matches_reference};
 
     const EXAMPLE_INPUT: &str = "seeds: 79 14 55 13
 
@@ -180,4 +220,62 @@ humidity-to-location map:
     fn example_part2() {
         assert_eq!(solve_part2(&prepare(EXAMPLE_INPUT)), 46);
     }
+
+    fn apply(mapping: &[(u64, u64, u64)], value: u64) -> u64 {
+        for &(output_start, input_start, length) in mapping {
+            if (input_start..input_start + length).contains(&value) {
+                return value - input_start + output_start;
+            }
+        }
+        value
+    }
+    fn reference(input: &str) -> (u64, u64) {
+        let (seeds, maps) = input.split_once("\n\n").unwrap();
+        let seeds = seeds["seeds: ".len()..]
+            .split(' ')
+            .map(|n| n.parse().unwrap())
+            .collect::<Vec<u64>>();
+        let mappings = maps
+            .split("\n\n")
+            .map(|map| {
+                map.lines()
+                    .skip(1)
+                    .map(|line| {
+                        let n = line
+                            .split(' ')
+                            .map(|n| n.parse().unwrap())
+                            .collect::<Vec<u64>>();
+                        (n[0], n[1], n[2])
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let location = |seed| {
+            mappings
+                .iter()
+                .fold(seed, |value, mapping| apply(mapping, value))
+        };
+        let part1 = seeds.iter().map(|&seed| location(seed)).min().unwrap();
+        let part2 = seeds
+            .chunks_exact(2)
+            .flat_map(|chunk| chunk[0]..chunk[0] + chunk[1])
+            .map(location)
+            .min()
+            .unwrap();
+        (part1, part2)
+    }
+    #[test]
+    fn matches_reference() {
+        // Small inputs have few ranges but values land on range boundaries often.
+        assert_matches_reference(
+            generate::day05::generate,
+            200,
+            &[0.02, 0.05, 0.1],
+            |input| {
+                let prepared = prepare(input);
+                (solve_part1(&prepared), solve_part2(&prepared))
+            },
+            reference,
+        );
+    }
 }
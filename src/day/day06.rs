@@ -1,3 +1,4 @@
+use crate::error::Error;
 use crate::solution::Solution;
 use std::mem::swap;
 use winnow::ascii::{dec_uint, digit1, space1};
@@ -10,8 +11,8 @@ pub struct Race {
     distance_mm: u64,
 }
 
-pub fn prepare_part1(input: &str) -> Vec<Race> {
-    separated_pair(
+pub fn try_prepare_part1(input: &str) -> Result<Vec<Race>, Error> {
+    let races: Vec<Race> = separated_pair(
         preceded(
             ("Time:", space1),
             separated(1.., dec_uint::<_, u64, ()>, space1),
@@ -22,6 +23,7 @@ pub fn prepare_part1(input: &str) -> Vec<Race> {
             separated(1.., dec_uint::<_, u64, ()>, space1),
         ),
     )
+    .verify(|(times, distances): &(Vec<_>, Vec<_>)| times.len() == distances.len())
     .map(|(times, distances): (Vec<_>, Vec<_>)| {
         times
             .into_iter()
@@ -33,19 +35,32 @@ pub fn prepare_part1(input: &str) -> Vec<Race> {
             .collect::<Vec<_>>()
     })
     .parse(input)
-    .unwrap()
+    .map_err(|err| Error::parse_at(input.as_bytes(), err.offset()))?;
+
+    // The product of the ways to win is at most the product of the number of ways to hold.
+    races
+        .iter()
+        .try_fold(1u64, |product, race| {
+            product.checked_mul(race.time_ms.checked_add(1)?)
+        })
+        .ok_or(Error::Invalid("too many ways to win"))?;
+    Ok(races)
 }
 
-pub fn prepare_part2(input: &str) -> Race {
-    separated_pair(
+pub fn prepare_part1(input: &str) -> Vec<Race> {
+    try_prepare_part1(input).unwrap()
+}
+
+pub fn try_prepare_part2(input: &str) -> Result<Race, Error> {
+    let race = separated_pair(
         preceded(
             ("Time:", space1::<_, ()>),
-            separated(1.., digit1, space1).map(|s: String| s.parse().unwrap()),
+            separated(1.., digit1, space1).try_map(|s: String| s.parse()),
         ),
         '\n',
         preceded(
             ("Distance:", space1::<_, ()>),
-            separated(1.., digit1, space1).map(|s: String| s.parse().unwrap()),
+            separated(1.., digit1, space1).try_map(|s: String| s.parse()),
         ),
     )
     .map(|(time_ms, distance_mm): (u64, u64)| Race {
@@ -53,7 +68,13 @@ pub fn prepare_part2(input: &str) -> Race {
         distance_mm,
     })
     .parse(input)
-    .unwrap()
+    .map_err(|err| Error::parse_at(input.as_bytes(), err.offset()))?;
+
+    Ok(race)
+}
+
+pub fn prepare_part2(input: &str) -> Race {
+    try_prepare_part2(input).unwrap()
 }
 
 fn solve_quadratic(a: f64, b: f64, c: f64) -> (f64, f64) {
@@ -71,24 +92,30 @@ pub fn solve_part(input: &[Race]) -> u64 {
                 swap(&mut a, &mut b);
             }
 
-            let first_win = a.floor() as u64 + 1;
-            let last_win = b.ceil() as u64 - 1;
-            last_win - first_win + 1
+            // Saturating, as rounding errors for large numbers must not turn into overflows.
+            let first_win = (a.floor() as u64).saturating_add(1);
+            let last_win = (b.ceil() as u64).saturating_sub(1);
+            (last_win + 1).saturating_sub(first_win)
         })
         .reduce(|acc, c| acc * c)
         .unwrap()
 }
 
+pub fn try_solve(input: &str) -> Result<(Solution, Solution), Error> {
+    Ok((
+        solve_part(&try_prepare_part1(input)?).into(),
+        solve_part(&[try_prepare_part2(input)?]).into(),
+    ))
+}
+
 pub fn solve(input: &str) -> (Solution, Solution) {
-    (
-        solve_part(&prepare_part1(input)).into(),
-        solve_part(&[prepare_part2(input)]).into(),
-    )
+    try_solve(input).unwrap()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::generate::{self, assert_matches_reference};
 
     const EXAMPLE_INPUT: &str = "Time:      7  15   30
 Distance:  9  40  200";
@@ -114,4 +141,48 @@ Distance:  9  40  200";
     fn example_part2() {
         assert_eq!(solve_part(&[prepare_part2(EXAMPLE_INPUT)]), 71503);
     }
+
+    #[test]
+    fn unwinnable_race() {
+        let races = prepare_part1("Time: 7 2\nDistance: 9 1");
+        assert_eq!(solve_part(&races[..1]), 4);
+        assert_eq!(solve_part(&races[1..]), 0);
+        assert_eq!(solve_part(&races), 0);
+    }
+
+    fn ways_to_win(time: u64, distance: u64) -> u64 {
+        (0..=time)
+            .filter(|hold| hold * (time - hold) > distance)
+            .count() as u64
+    }
+    fn reference(input: &str) -> (u64, u64) {
+        let (times, distances) = input.split_once('\n').unwrap();
+        let times = times.split_whitespace().skip(1).collect::<Vec<_>>();
+        let distances = distances.split_whitespace().skip(1).collect::<Vec<_>>();
+        let part1 = times
+            .iter()
+            .zip(&distances)
+            .map(|(time, distance)| ways_to_win(time.parse().unwrap(), distance.parse().unwrap()))
+            .product();
+        let part2 = ways_to_win(
+            times.concat().parse().unwrap(),
+            distances.concat().parse().unwrap(),
+        );
+        (part1, part2)
+    }
+    #[test]
+    fn matches_reference() {
+        assert_matches_reference(
+            generate::day06::generate,
+            20,
+            &[0.75],
+            |input| {
+                (
+                    solve_part(&prepare_part1(input)),
+                    solve_part(&[prepare_part2(input)]),
+                )
+            },
+            reference,
+        );
+    }
 }
@@ -1,3 +1,4 @@
+use crate::error::Error;
 use crate::solution::Solution;
 use bstr::ByteSlice;
 use winnow::ascii::dec_uint;
@@ -7,30 +8,46 @@ type Hand = u32;
 
 type PreparedInput = Vec<(Hand, u16)>;
 
-pub fn prepare(input: &str) -> PreparedInput {
+pub fn try_prepare(input: &str) -> Result<PreparedInput, Error> {
     input
         .as_bytes()
         .lines()
-        .map(|line| {
-            let hand: Hand = line[0..5].iter().fold(0u32, |num, c| {
-                (num << 4)
-                    + match c {
+        .enumerate()
+        .map(|(i, line)| {
+            let error_at = |column: usize| Error::Parse {
+                line: i + 1,
+                column: column + 1,
+            };
+            if line.len() < 7 || line[5] != b' ' {
+                return Err(error_at(line.len().min(5)));
+            }
+            let hand: Hand = line[0..5]
+                .iter()
+                .enumerate()
+                .try_fold(0u32, |num, (column, c)| {
+                    let card = match c {
                         b'2'..=b'9' => (c - b'0') as u32,
                         b'A' => 14,
                         b'K' => 13,
                         b'Q' => 12,
                         b'J' => 11,
                         b'T' => 10,
-                        _ => panic!(),
-                    }
-            });
-
-            let bid: u16 = dec_uint::<_, _, ()>.parse(&line[6..]).unwrap();
-            (hand, bid)
+                        _ => return Err(error_at(column)),
+                    };
+                    Ok((num << 4) + card)
+                })?;
+            let bid: u16 = dec_uint::<_, _, ()>
+                .parse(&line[6..])
+                .map_err(|err| error_at(6 + err.offset()))?;
+            Ok((hand, bid))
         })
         .collect()
 }
 
+pub fn prepare(input: &str) -> PreparedInput {
+    try_prepare(input).unwrap()
+}
+
 fn top_two<T, I>(list: I, min: T) -> [T; 2]
 where
     T: Copy + Ord,
@@ -97,14 +114,19 @@ pub fn solve_part2(input: &PreparedInput) -> u64 {
     solve_joker::<true>(input)
 }
 
+pub fn try_solve(input: &str) -> Result<(Solution, Solution), Error> {
+    let input = try_prepare(input)?;
+    Ok((solve_part1(&input).into(), solve_part2(&input).into()))
+}
+
 pub fn solve(input: &str) -> (Solution, Solution) {
-    let input = prepare(input);
-    (solve_part1(&input).into(), solve_part2(&input).into())
+    try_solve(input).unwrap()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::generate::{self, assert_matches_reference};
 
     const EXAMPLE_INPUT: &str = "32T3K 765
 T55J5 684
@@ -123,4 +145,72 @@ QQQJA 483";
     fn example_part2() {
         assert_eq!(solve_part2(&prepare(EXAMPLE_INPUT)), 5905);
     }
+
+    /// Card counts in decreasing order, which compare in the same order as the hand types.
+    fn hand_type(cards: &[u8]) -> Vec<usize> {
+        let mut counts = cards
+            .iter()
+            .map(|card| cards.iter().filter(|&other| other == card).count())
+            .collect::<Vec<_>>();
+        counts.sort_unstable_by(|a, b| b.cmp(a));
+        counts
+    }
+    /// Best type over every possible replacement of each joker.
+    fn joker_hand_type(cards: &[u8]) -> Vec<usize> {
+        match cards.iter().position(|&card| card == b'J') {
+            None => hand_type(cards),
+            Some(i) => b"23456789TQKA"
+                .iter()
+                .map(|&replacement| {
+                    let mut cards = cards.to_vec();
+                    cards[i] = replacement;
+                    joker_hand_type(&cards)
+                })
+                .max()
+                .unwrap(),
+        }
+    }
+    fn reference(input: &str, joker: bool) -> u64 {
+        let order: &[u8] = if joker {
+            b"J23456789TQKA"
+        } else {
+            b"23456789TJQKA"
+        };
+        let mut hands = input
+            .lines()
+            .map(|line| {
+                let (cards, bid) = line.split_once(' ').unwrap();
+                let cards = cards.as_bytes();
+                let kind = if joker {
+                    joker_hand_type(cards)
+                } else {
+                    hand_type(cards)
+                };
+                let values = cards
+                    .iter()
+                    .map(|card| order.iter().position(|c| c == card).unwrap())
+                    .collect::<Vec<_>>();
+                ((kind, values), bid.parse::<u64>().unwrap())
+            })
+            .collect::<Vec<_>>();
+        hands.sort();
+        hands
+            .iter()
+            .enumerate()
+            .map(|(i, (_, bid))| (i as u64 + 1) * bid)
+            .sum()
+    }
+    #[test]
+    fn matches_reference() {
+        assert_matches_reference(
+            generate::day07::generate,
+            20,
+            &[0.1],
+            |input| {
+                let prepared = prepare(input);
+                (solve_part1(&prepared), solve_part2(&prepared))
+            },
+            |input| (reference(input, false), reference(input, true)),
+        );
+    }
 }
@@ -1,9 +1,12 @@
+use crate::error::Error;
 use crate::solution::Solution;
-use num::integer::lcm;
+use crate::util::solver::find_cycle_hashed;
+use num::integer::gcd;
 use rustc_hash::FxHashMap;
-use winnow::ascii::alphanumeric1;
 use winnow::combinator::{alt, delimited, repeat, separated, separated_pair};
-use winnow::Parser;
+use winnow::stream::AsChar;
+use winnow::token::take_while;
+use winnow::{PResult, Parser};
 
 #[derive(Copy, Clone, Debug)]
 pub enum Direction {
@@ -27,8 +30,14 @@ impl Node {
 
 type PreparedInput = (Vec<Direction>, FxHashMap<Node, (Node, Node)>);
 
-pub fn prepare(input: &str) -> PreparedInput {
-    separated_pair(
+fn parse_node(input: &mut &[u8]) -> PResult<Node, ()> {
+    take_while(3, AsChar::is_alphanum)
+        .map(Node::from_u8_slice)
+        .parse_next(input)
+}
+
+pub fn try_prepare(input: &str) -> Result<PreparedInput, Error> {
+    let (directions, map): PreparedInput = separated_pair(
         repeat(
             1..,
             alt(("L".value(Direction::Left), "R".value(Direction::Right))),
@@ -37,32 +46,42 @@ pub fn prepare(input: &str) -> PreparedInput {
         separated(
             1..,
             separated_pair(
-                alphanumeric1::<_, ()>.map(Node::from_u8_slice),
+                parse_node,
                 " = ",
-                delimited(
-                    "(",
-                    separated_pair(
-                        alphanumeric1.map(Node::from_u8_slice),
-                        ", ",
-                        alphanumeric1.map(Node::from_u8_slice),
-                    ),
-                    ")",
-                ),
+                delimited("(", separated_pair(parse_node, ", ", parse_node), ")"),
             ),
             "\n",
         ),
     )
     .parse(input.as_bytes())
-    .unwrap()
+    .map_err(|err| Error::parse_at(input.as_bytes(), err.offset()))?;
+
+    if map
+        .values()
+        .any(|(left, right)| !map.contains_key(left) || !map.contains_key(right))
+    {
+        return Err(Error::Invalid("every node has to be defined"));
+    }
+    Ok((directions, map))
 }
 
-pub fn solve_part1(input: &PreparedInput) -> usize {
+pub fn prepare(input: &str) -> PreparedInput {
+    try_prepare(input).unwrap()
+}
+
+pub fn try_solve_part1(input: &PreparedInput) -> Result<usize, Error> {
     let (directions, map) = input;
 
     let mut current = Node::from_u8_slice("AAA".as_bytes());
+    if !map.contains_key(&current) {
+        return Err(Error::Invalid("there has to be an AAA node"));
+    }
+    // Once every node has been visited at every instruction the walk only repeats itself.
+    let step_limit = map.len() * directions.len();
     directions
         .iter()
         .cycle()
+        .take(step_limit)
         .position(|direction| {
             let (left, right) = map[&current];
 
@@ -72,62 +91,75 @@ pub fn solve_part1(input: &PreparedInput) -> usize {
             };
             current == Node::from_u8_slice("ZZZ".as_bytes())
         })
-        .unwrap()
-        + 1
+        .map(|steps| steps + 1)
+        .ok_or(Error::Invalid("ZZZ has to be reachable from AAA"))
 }
 
-pub fn solve_part2(input: &PreparedInput) -> usize {
+pub fn solve_part1(input: &PreparedInput) -> usize {
+    try_solve_part1(input).unwrap()
+}
+
+pub fn try_solve_part2(input: &PreparedInput) -> Result<usize, Error> {
     let (directions, map) = input;
 
     map.keys()
         .filter(|key| key.ends_with(b'A'))
-        .map(|mut current| {
-            let mut encountered = vec![FxHashMap::default(); directions.len() + 1];
-            encountered[0].insert(current.to_owned(), 0);
-
-            let mut encountered_z_distance = vec![];
-            let (cycle_start, cycle_distance) = (0..)
-                .find_map(|i| {
-                    let direction_offset = i % directions.len();
-                    let direction = directions[direction_offset];
-                    let (left, right) = &map[current];
-
-                    current = match direction {
-                        Direction::Left => left,
-                        Direction::Right => right,
-                    };
-                    if current.ends_with(b'Z') {
-                        encountered_z_distance.push(i + 1);
-                    }
-                    encountered[direction_offset + 1]
-                        .insert(current.to_owned(), i + 1)
-                        .map(|existing| (existing, i + 1))
-                })
-                .unwrap();
-
-            (cycle_start, cycle_distance, encountered_z_distance)
-        })
-        .map(|(cycle_start, cycle_distance, encountered_z_distance)| {
+        .map(|&start| {
+            // The state is the node together with the position in the directions.
+            let step = |&(offset, node): &(usize, Node)| {
+                let (left, right) = map[&node];
+                let node = match directions[offset] {
+                    Direction::Left => left,
+                    Direction::Right => right,
+                };
+                ((offset + 1) % directions.len(), node)
+            };
+            let (cycle, states) = find_cycle_hashed((0, start), step, |&state| state);
+
             // For this problem it just so happens that the distance at which Z is encountered exactly matches the cycle size
             // This problem would be (significantly?) harder if that was not the case
             // Outside the example there is also just one valid Z distance found.
             // This further simplifies the problem to a simple least common multiple
-            let z_distance = *encountered_z_distance.last().unwrap();
-            assert_eq!(z_distance, cycle_distance - cycle_start);
-            z_distance
+            let last_z_distance = (1..=cycle.start + cycle.period)
+                .rfind(|&i| states[cycle.equivalent_step(i)].1.ends_with(b'Z'));
+            match last_z_distance {
+                Some(z_distance) if z_distance == cycle.period => Ok(z_distance),
+                _ => Err(Error::Invalid("ghosts have to reach Z once per loop")),
+            }
         })
-        .reduce(lcm)
-        .unwrap()
+        .try_fold(None, |lcm, z_distance| {
+            let z_distance = z_distance?;
+            let Some(lcm) = lcm else {
+                return Ok(Some(z_distance));
+            };
+            (lcm / gcd(lcm, z_distance))
+                .checked_mul(z_distance)
+                .map(Some)
+                .ok_or(Error::Invalid("too many steps"))
+        })?
+        .ok_or(Error::Invalid("there has to be a node ending with A"))
+}
+
+pub fn solve_part2(input: &PreparedInput) -> usize {
+    try_solve_part2(input).unwrap()
+}
+
+pub fn try_solve(input: &str) -> Result<(Solution, Solution), Error> {
+    let input = try_prepare(input)?;
+    Ok((
+        try_solve_part1(&input)?.into(),
+        try_solve_part2(&input)?.into(),
+    ))
 }
 
 pub fn solve(input: &str) -> (Solution, Solution) {
-    let input = prepare(input);
-    (solve_part1(&input).into(), solve_part2(&input).into())
+    try_solve(input).unwrap()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::generate::{self, assert_matches_reference};
 
     const EXAMPLE_INPUT: &str = "RL
 
@@ -165,4 +197,61 @@ XXX = (XXX, XXX)"
             6
         );
     }
+
+    fn reference(input: &str) -> (usize, usize) {
+        let (directions, nodes) = input.split_once("\n\n").unwrap();
+        let network = nodes
+            .lines()
+            .map(|line| (&line[0..3], (&line[7..10], &line[12..15])))
+            .collect::<std::collections::HashMap<_, _>>();
+        let step = |node: &str, direction: u8| {
+            let (left, right) = network[node];
+            if direction == b'L' {
+                left
+            } else {
+                right
+            }
+        };
+
+        let mut node = "AAA";
+        let mut part1 = 0;
+        for direction in directions.bytes().cycle() {
+            node = step(node, direction);
+            part1 += 1;
+            if node == "ZZZ" {
+                break;
+            }
+        }
+
+        // Moves every ghost in lockstep until they are all on a Z node at once.
+        let mut ghosts = network
+            .keys()
+            .filter(|node| node.ends_with('A'))
+            .copied()
+            .collect::<Vec<_>>();
+        let mut part2 = 0;
+        for direction in directions.bytes().cycle() {
+            for ghost in ghosts.iter_mut() {
+                *ghost = step(ghost, direction);
+            }
+            part2 += 1;
+            if ghosts.iter().all(|ghost| ghost.ends_with('Z')) {
+                break;
+            }
+        }
+        (part1, part2)
+    }
+    #[test]
+    fn matches_reference() {
+        assert_matches_reference(
+            generate::day08::generate,
+            10,
+            &[0.4],
+            |input| {
+                let prepared = prepare(input);
+                (solve_part1(&prepared), solve_part2(&prepared))
+            },
+            reference,
+        );
+    }
 }
@@ -1,14 +1,59 @@
+use crate::error::Error;
 use crate::solution::Solution;
 use bstr::ByteSlice;
 use winnow::ascii::dec_int;
 use winnow::combinator::separated;
 use winnow::prelude::*;
 
-pub fn prepare(input: &str) -> impl Iterator<Item = Vec<i32>> + '_ {
-    input.as_bytes().lines().map(|line| {
+pub fn try_prepare(input: &str) -> impl Iterator<Item = Result<Vec<i32>, Error>> + '_ {
+    input.as_bytes().lines().enumerate().map(|(i, line)| {
         separated(1.., dec_int::<_, i32, ()>, " ")
             .parse(line)
-            .unwrap()
+            .map_err(|err| Error::Parse {
+                line: i + 1,
+                column: err.offset() + 1,
+            })
+    })
+}
+
+pub fn prepare(input: &str) -> impl Iterator<Item = Vec<i32>> + '_ {
+    try_prepare(input).map(Result::unwrap)
+}
+
+/// Next and previous value of the sequence, or `None` if any of the differences overflow.
+fn extrapolate(mut sequence: Vec<i32>) -> Option<(i32, i32)> {
+    let mut first_value = sequence[0];
+    let mut last_value = sequence[sequence.len() - 1];
+    let mut i = 0;
+    loop {
+        for i in 0..sequence.len() - 1 {
+            sequence[i] = sequence[i + 1].checked_sub(sequence[i])?;
+        }
+        sequence.pop();
+        if sequence.iter().all(|num| *num == 0) {
+            break Some((last_value, first_value));
+        }
+        if i % 2 == 0 {
+            first_value = first_value.checked_sub(sequence[0])?;
+        } else {
+            first_value = first_value.checked_add(sequence[0])?;
+        }
+        last_value = last_value.checked_add(sequence[sequence.len() - 1])?;
+        i += 1;
+    }
+}
+
+pub fn try_solve_both_parts<Input>(mut input: Input) -> Result<(i32, i32), Error>
+where
+    Input: Iterator<Item = Result<Vec<i32>, Error>>,
+{
+    const OVERFLOW: Error = Error::Invalid("values have to fit in an i32");
+    input.try_fold((0i32, 0i32), |(part1_sum, part2_sum), sequence| {
+        let (part1, part2) = extrapolate(sequence?).ok_or(OVERFLOW)?;
+        Ok((
+            part1_sum.checked_add(part1).ok_or(OVERFLOW)?,
+            part2_sum.checked_add(part2).ok_or(OVERFLOW)?,
+        ))
     })
 }
 
@@ -16,41 +61,22 @@ pub fn solve_both_parts<Input>(input: Input) -> (i32, i32)
 where
     Input: Iterator<Item = Vec<i32>>,
 {
-    input
-        .map(|mut sequence| -> (i32, i32) {
-            let mut first_value = sequence[0];
-            let mut last_value = sequence[sequence.len() - 1];
-            let mut i = 0;
-            loop {
-                for i in 0..sequence.len() - 1 {
-                    sequence[i] = sequence[i + 1] - sequence[i];
-                }
-                sequence.pop();
-                if sequence.iter().all(|num| *num == 0) {
-                    break (last_value, first_value);
-                }
-                if i % 2 == 0 {
-                    first_value -= sequence[0];
-                } else {
-                    first_value += sequence[0];
-                }
-                last_value += sequence[sequence.len() - 1];
-                i += 1;
-            }
-        })
-        .reduce(|(part1_sum, part2_sum), (part1, part2)| (part1_sum + part1, part2_sum + part2))
-        .unwrap()
+    try_solve_both_parts(input.map(Ok)).unwrap()
+}
+
+pub fn try_solve(input: &str) -> Result<(Solution, Solution), Error> {
+    let output = try_solve_both_parts(try_prepare(input))?;
+    Ok((output.0.into(), output.1.into()))
 }
 
 pub fn solve(input: &str) -> (Solution, Solution) {
-    let input = prepare(input);
-    let output = solve_both_parts(input);
-    (output.0.into(), output.1.into())
+    try_solve(input).unwrap()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::generate::{self, assert_matches_reference};
 
     const EXAMPLE_INPUT: &str = "0 3 6 9 12 15
 1 3 6 10 15 21
@@ -67,4 +93,42 @@ mod tests {
     fn example_part2() {
         assert_eq!(solve_both_parts(prepare(EXAMPLE_INPUT)).1, 2);
     }
+
+    /// Builds the complete difference table and extrapolates it at both ends.
+    fn reference(input: &str) -> (i64, i64) {
+        input.lines().fold((0, 0), |(part1, part2), line| {
+            let mut rows = vec![line
+                .split(' ')
+                .map(|num| num.parse::<i64>().unwrap())
+                .collect::<Vec<_>>()];
+            while rows.last().unwrap().iter().any(|&num| num != 0) {
+                let differences = rows
+                    .last()
+                    .unwrap()
+                    .windows(2)
+                    .map(|pair| pair[1] - pair[0])
+                    .collect();
+                rows.push(differences);
+            }
+            let (mut next, mut previous) = (0, 0);
+            for row in rows.iter().rev() {
+                next += row[row.len() - 1];
+                previous = row[0] - previous;
+            }
+            (part1 + next, part2 + previous)
+        })
+    }
+    #[test]
+    fn matches_reference() {
+        assert_matches_reference(
+            generate::day09::generate,
+            20,
+            &[0.1],
+            |input| {
+                let (part1, part2) = solve_both_parts(prepare(input));
+                (part1 as i64, part2 as i64)
+            },
+            reference,
+        );
+    }
 }
@@ -1,6 +1,9 @@
+use crate::error::Error;
 use crate::solution::Solution;
-use crate::util::grid::{BackedGrid, Grid};
+use crate::util::bitgrid::BitGrid;
+use crate::util::grid::{check_rows, flood_fill, BackedGrid, Connectivity, GridView};
 use crate::util::position::{Direction, Position, RotationalDirection};
+use crate::util::render::{Color, Renderer};
 
 #[derive(PartialEq, Copy, Clone, Debug)]
 pub enum Element {
@@ -36,51 +39,53 @@ pub fn prepare(input: &str) -> PreparedInput<'_> {
     BackedGrid::from_data_and_row_separator(input.as_bytes(), b'\n')
 }
 
-fn calc_loop(grid: &PreparedInput) -> Vec<(Position, Direction, Direction)> {
+pub fn try_prepare(input: &str) -> Result<PreparedInput<'_>, Error> {
+    check_rows(input.as_bytes(), |c| b".S|-LJ7F".contains(&c))?;
+    if input.bytes().filter(|&c| c == b'S').count() != 1 {
+        return Err(Error::Invalid("there has to be exactly one start"));
+    }
+    Ok(prepare(input))
+}
+
+const BROKEN_LOOP: Error = Error::Invalid("the pipes from the start have to form a loop");
+
+fn calc_loop(grid: &PreparedInput) -> Result<Vec<(Position, Direction, Direction)>, Error> {
     let (start_pos, _) = grid
-        .iter::<Element>()
+        .iter_as::<Element>()
         .find(|(_, elem)| *elem == Element::Start)
-        .unwrap();
+        .ok_or(Error::Invalid("there has to be a start"))?;
 
-    let dimensions = grid.dimensions.into();
+    let dimensions = grid.dimensions;
 
+    let connects = |dy, dx, elements: [Element; 3]| {
+        grid.get_offset(&start_pos, dy, dx)
+            .is_some_and(|c| elements.contains(&Element::from(c)))
+    };
     let neighbours = [
         // Up
-        start_pos
-            .checked_moved(&dimensions, &Direction::Up)
-            .is_some_and(|pos| {
-                matches!(
-                    grid.get(&pos),
-                    Element::Vertical | Element::SouthEast | Element::SouthWest
-                )
-            }),
+        connects(
+            -1,
+            0,
+            [Element::Vertical, Element::SouthEast, Element::SouthWest],
+        ),
         // Down
-        start_pos
-            .checked_moved(&dimensions, &Direction::Down)
-            .is_some_and(|pos| {
-                matches!(
-                    grid.get(&pos),
-                    Element::Vertical | Element::NorthEast | Element::NorthWest
-                )
-            }),
+        connects(
+            1,
+            0,
+            [Element::Vertical, Element::NorthEast, Element::NorthWest],
+        ),
         // Right
-        start_pos
-            .checked_moved(&dimensions, &Direction::Right)
-            .is_some_and(|pos| {
-                matches!(
-                    grid.get(&pos),
-                    Element::Horizontal | Element::NorthWest | Element::SouthWest
-                )
-            }),
+        connects(
+            0,
+            1,
+            [Element::Horizontal, Element::NorthWest, Element::SouthWest],
+        ),
         // Left
-        start_pos
-            .checked_moved(&dimensions, &Direction::Left)
-            .is_some_and(|pos| {
-                matches!(
-                    grid.get(&pos),
-                    Element::Horizontal | Element::NorthEast | Element::SouthEast
-                )
-            }),
+        connects(
+            0,
+            -1,
+            [Element::Horizontal, Element::NorthEast, Element::SouthEast],
+        ),
     ];
 
     let (mut incoming_direction, mut outgoing_direction) = match neighbours {
@@ -90,7 +95,11 @@ fn calc_loop(grid: &PreparedInput) -> Vec<(Position, Direction, Direction)> {
         [true, false, false, true] => (Direction::Down, Direction::Left),
         [false, true, true, false] => (Direction::Up, Direction::Right),
         [false, true, false, true] => (Direction::Up, Direction::Left),
-        _ => panic!(),
+        _ => {
+            return Err(Error::Invalid(
+                "the start has to connect to exactly two pipes",
+            ))
+        }
     };
 
     let mut pos = start_pos;
@@ -98,40 +107,42 @@ fn calc_loop(grid: &PreparedInput) -> Vec<(Position, Direction, Direction)> {
     loop {
         visited.push((pos, incoming_direction, outgoing_direction));
 
-        pos = pos.checked_moved(&dimensions, &outgoing_direction).unwrap();
+        pos = pos
+            .checked_moved(&dimensions, &outgoing_direction)
+            .ok_or(BROKEN_LOOP)?;
         incoming_direction = outgoing_direction;
         outgoing_direction = match incoming_direction {
-            Direction::Up => match grid.get(&pos) {
+            Direction::Up => match grid.get_as(&pos) {
                 Element::Start => break,
                 Element::Vertical => Direction::Up,
                 Element::SouthWest => Direction::Left,
                 Element::SouthEast => Direction::Right,
-                _ => panic!(),
+                _ => return Err(BROKEN_LOOP),
             },
-            Direction::Down => match grid.get(&pos) {
+            Direction::Down => match grid.get_as(&pos) {
                 Element::Start => break,
                 Element::Vertical => Direction::Down,
                 Element::NorthWest => Direction::Left,
                 Element::NorthEast => Direction::Right,
-                _ => panic!(),
+                _ => return Err(BROKEN_LOOP),
             },
-            Direction::Right => match grid.get(&pos) {
+            Direction::Right => match grid.get_as(&pos) {
                 Element::Start => break,
                 Element::Horizontal => Direction::Right,
                 Element::SouthWest => Direction::Down,
                 Element::NorthWest => Direction::Up,
-                _ => panic!(),
+                _ => return Err(BROKEN_LOOP),
             },
-            Direction::Left => match grid.get(&pos) {
+            Direction::Left => match grid.get_as(&pos) {
                 Element::Start => break,
                 Element::Horizontal => Direction::Left,
                 Element::NorthEast => Direction::Up,
                 Element::SouthEast => Direction::Down,
-                _ => panic!(),
+                _ => return Err(BROKEN_LOOP),
             },
         };
     }
-    visited
+    Ok(visited)
 }
 
 pub fn solve_part1(grid: &PreparedInput) -> usize {
@@ -143,8 +154,17 @@ pub fn solve_part2(grid: &PreparedInput) -> usize {
 }
 
 fn solve_parts(grid: &PreparedInput) -> (usize, usize) {
-    let visited = calc_loop(grid);
-    let part1_result = visited.len() / 2;
+    try_solve_parts(grid).unwrap()
+}
+
+fn try_solve_parts(grid: &PreparedInput) -> Result<(usize, usize), Error> {
+    let (visited_set, enclosed_entries) = loop_and_enclosed(grid)?;
+    Ok((visited_set.count() / 2, enclosed_entries.count()))
+}
+
+/// The tiles of the loop and the tiles enclosed by it.
+fn loop_and_enclosed(grid: &PreparedInput) -> Result<(BitGrid, BitGrid), Error> {
+    let visited = calc_loop(grid)?;
 
     let clockwise_count = visited
         .iter()
@@ -162,46 +182,66 @@ fn solve_parts(grid: &PreparedInput) -> (usize, usize) {
     let rot_direction = match clockwise_count {
         4 => RotationalDirection::Clockwise,
         -4 => RotationalDirection::Anticlockwise,
-        _ => panic!(),
+        _ => return Err(BROKEN_LOOP),
     };
 
-    let mut visited_set = Grid::from_dimensions(grid.dimensions, false);
-    visited_set.extend(visited.iter().map(|(pos, _, _)| *pos));
-
-    let mut enclosed_entries = Grid::from_dimensions(grid.dimensions, false);
+    let mut visited_set = BitGrid::from_dimensions(grid.dimensions);
+    for (pos, _, _) in &visited {
+        visited_set.insert(pos);
+    }
 
-    let dimensions = grid.dimensions.into();
-    let mut search = |mut pos: Position, direction_to_search: &Direction| {
-        while {
-            pos = pos.checked_moved(&dimensions, direction_to_search).unwrap();
-            !visited_set.contains(&pos)
-        } {
-            enclosed_entries.set(&pos, true);
+    // Every enclosed region borders the loop, so filling from the tiles on the inner side of the
+    // loop reaches all of them.
+    let dimensions = grid.dimensions;
+    let mut inner_neighbours = Vec::with_capacity(2 * visited.len());
+    for (pos, incoming_direction, outgoing_direction) in visited {
+        for direction in [incoming_direction, outgoing_direction] {
+            let inner = pos.checked_moved(&dimensions, &direction.with_rotation(&rot_direction));
+            inner_neighbours.extend(inner);
         }
-    };
+    }
+    let enclosed_entries = flood_fill(grid, inner_neighbours, Connectivity::Four, |pos, _| {
+        !visited_set.get(pos)
+    });
 
-    visited
-        .into_iter()
-        .for_each(|(pos, incoming_direction, outgoing_direction)| {
-            search(pos, &incoming_direction.with_rotation(&rot_direction));
-            if incoming_direction != outgoing_direction {
-                search(pos, &outgoing_direction.with_rotation(&rot_direction));
-            }
-        });
+    Ok((visited_set, enclosed_entries))
+}
 
-    (part1_result, enclosed_entries.count())
+/// Draws the pipes with the loop and the enclosed tiles highlighted.
+pub fn render(input: &str) -> Result<Renderer, Error> {
+    let grid = try_prepare(input)?;
+    let (visited_set, enclosed_entries) = loop_and_enclosed(&grid)?;
+    let renderer = Renderer::new(&grid, |c| match Element::from(c) {
+        Element::None => '.',
+        Element::Start => 'S',
+        Element::Vertical => '│',
+        Element::Horizontal => '─',
+        Element::NorthEast => '└',
+        Element::NorthWest => '┘',
+        Element::SouthWest => '┐',
+        Element::SouthEast => '┌',
+    });
+    Ok(renderer
+        .overlay(Color::Yellow, visited_set.iter())
+        .overlay_glyph(Color::Green, 'I', enclosed_entries.iter()))
 }
 
-pub fn solve(input: &str) -> (Solution, Solution) {
-    let input = prepare(input);
-    let (a, b) = solve_parts(&input);
+pub fn try_solve(input: &str) -> Result<(Solution, Solution), Error> {
+    let input = try_prepare(input)?;
+    let (a, b) = try_solve_parts(&input)?;
+
+    Ok((a.into(), b.into()))
+}
 
-    (a.into(), b.into())
+pub fn solve(input: &str) -> (Solution, Solution) {
+    try_solve(input).unwrap()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::generate::{self, assert_matches_reference};
+    use crate::util::position::Dimensions;
 
     const EXAMPLE1: &str = "-L|F7
 7S-7|
@@ -215,14 +255,25 @@ SJLL7
 LJ.LJ";
     #[test]
     fn example_prepare() {
-        assert_eq!(prepare(EXAMPLE1).dimensions, (5, 5));
-        assert_eq!(prepare(EXAMPLE2).dimensions, (5, 5));
+        assert_eq!(
+            prepare(EXAMPLE1).dimensions,
+            Dimensions::from_height_width(5, 5)
+        );
+        assert_eq!(
+            prepare(EXAMPLE2).dimensions,
+            Dimensions::from_height_width(5, 5)
+        );
     }
     #[test]
     fn example_part1() {
         assert_eq!(solve_part1(&prepare(EXAMPLE1)), 4);
         assert_eq!(solve_part1(&prepare(EXAMPLE2)), 8);
     }
+    #[test]
+    fn example_render() {
+        let renderer = render(EXAMPLE1).unwrap().with_color(false);
+        assert_eq!(renderer.to_string(), "─└│┌┐\n┐S─┐│\n└│I││\n─└─┘│\n└│─┘┌\n");
+    }
 
     const EXAMPLE3: &str = "...........
 .S-------7.
@@ -271,4 +322,92 @@ L7JLJL-JLJLJL--JLJ.L";
         assert_eq!(solve_part2(&prepare(EXAMPLE5)), 8);
         assert_eq!(solve_part2(&prepare(EXAMPLE6)), 10);
     }
+
+    /// Offsets of the tiles a pipe connects to.
+    fn connections(pipe: u8) -> &'static [(isize, isize)] {
+        match pipe {
+            b'|' => &[(-1, 0), (1, 0)],
+            b'-' => &[(0, -1), (0, 1)],
+            b'L' => &[(-1, 0), (0, 1)],
+            b'J' => &[(-1, 0), (0, -1)],
+            b'7' => &[(1, 0), (0, -1)],
+            b'F' => &[(1, 0), (0, 1)],
+            _ => &[],
+        }
+    }
+    fn reference(input: &str) -> (usize, usize) {
+        let mut tiles = input
+            .lines()
+            .map(|line| line.as_bytes().to_vec())
+            .collect::<Vec<_>>();
+        let (height, width) = (tiles.len() as isize, tiles[0].len() as isize);
+        let tile = |tiles: &Vec<Vec<u8>>, (y, x): (isize, isize)| {
+            if (0..height).contains(&y) && (0..width).contains(&x) {
+                tiles[y as usize][x as usize]
+            } else {
+                b'.'
+            }
+        };
+        let start = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (y, x)))
+            .find(|&position| tile(&tiles, position) == b'S')
+            .unwrap();
+
+        // The start is the one pipe which connects to exactly the neighbours leading back to it.
+        let connects_back = |offset: (isize, isize)| {
+            let neighbour = (start.0 + offset.0, start.1 + offset.1);
+            connections(tile(&tiles, neighbour)).contains(&(-offset.0, -offset.1))
+        };
+        let start_pipe = *b"|-LJ7F"
+            .iter()
+            .find(|&&pipe| {
+                connections(pipe)
+                    .iter()
+                    .all(|&offset| connects_back(offset))
+            })
+            .unwrap();
+        tiles[start.0 as usize][start.1 as usize] = start_pipe;
+
+        let mut on_loop = vec![vec![false; width as usize]; height as usize];
+        let (mut previous, mut current) = (start, start);
+        loop {
+            on_loop[current.0 as usize][current.1 as usize] = true;
+            let next = connections(tile(&tiles, current))
+                .iter()
+                .map(|offset| (current.0 + offset.0, current.1 + offset.1))
+                .find(|&next| next != previous)
+                .unwrap();
+            (previous, current) = (current, next);
+            if current == start {
+                break;
+            }
+        }
+        let loop_length = on_loop.iter().flatten().filter(|&&tile| tile).count();
+
+        // A tile is enclosed when a ray to the left crosses the loop an odd number of times.
+        let mut enclosed = 0;
+        for (y, row) in tiles.iter().enumerate() {
+            let mut inside = false;
+            for (x, &pipe) in row.iter().enumerate() {
+                if on_loop[y][x] {
+                    if matches!(pipe, b'|' | b'L' | b'J') {
+                        inside = !inside;
+                    }
+                } else if inside {
+                    enclosed += 1;
+                }
+            }
+        }
+        (loop_length / 2, enclosed)
+    }
+    #[test]
+    fn matches_reference() {
+        assert_matches_reference(
+            generate::day10::generate,
+            20,
+            &[0.1],
+            |input| solve_parts(&prepare(input)),
+            reference,
+        );
+    }
 }
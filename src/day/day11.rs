@@ -1,4 +1,7 @@
+use crate::error::Error;
 use crate::solution::Solution;
+use crate::util::grid::check_rows;
+use crate::util::position::IVec2;
 use rustc_hash::FxHashSet;
 
 type PreparedInput = Vec<(usize, usize)>;
@@ -15,6 +18,15 @@ pub fn prepare(input: &str) -> PreparedInput {
         .collect::<Vec<_>>()
 }
 
+pub fn try_prepare(input: &str) -> Result<PreparedInput, Error> {
+    check_rows(input.as_bytes(), |c| c == b'.' || c == b'#')?;
+    let input = prepare(input);
+    if input.is_empty() {
+        return Err(Error::Invalid("there has to be a galaxy"));
+    }
+    Ok(input)
+}
+
 fn solve_with_dist(input: &PreparedInput, dist: usize) -> usize {
     let columns = input.iter().map(|(_, i)| *i).collect::<FxHashSet<_>>();
     let rows = input.iter().map(|(j, _)| *j).collect::<FxHashSet<_>>();
@@ -38,9 +50,9 @@ fn solve_with_dist(input: &PreparedInput, dist: usize) -> usize {
     let expanded = input
         .iter()
         .map(|(j, i)| {
-            (
-                *j + expansion_y[*j] * increase_factor,
-                *i + expansion_x[*i] * increase_factor,
+            IVec2::new(
+                (*j + expansion_y[*j] * increase_factor) as i64,
+                (*i + expansion_x[*i] * increase_factor) as i64,
             )
         })
         .collect::<FxHashSet<_>>();
@@ -52,8 +64,8 @@ fn solve_with_dist(input: &PreparedInput, dist: usize) -> usize {
                 .iter()
                 .filter_map(move |b| if a >= b { None } else { Some((a, b)) })
         })
-        .map(|(a, b)| a.0.abs_diff(b.0) + a.1.abs_diff(b.1))
-        .sum()
+        .map(|(a, b)| a.manhattan_distance(b))
+        .sum::<u64>() as usize
 }
 
 pub fn solve_part1(input: &PreparedInput) -> usize {
@@ -64,14 +76,19 @@ pub fn solve_part2(input: &PreparedInput) -> usize {
     solve_with_dist(input, 1_000_000)
 }
 
+pub fn try_solve(input: &str) -> Result<(Solution, Solution), Error> {
+    let input = try_prepare(input)?;
+    Ok((solve_part1(&input).into(), solve_part2(&input).into()))
+}
+
 pub fn solve(input: &str) -> (Solution, Solution) {
-    let input = prepare(input);
-    (solve_part1(&input).into(), solve_part2(&input).into())
+    try_solve(input).unwrap()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::generate::{self, assert_matches_reference};
 
     const EXAMPLE_INPUT: &str = "...#......
 .......#..
@@ -96,4 +113,42 @@ mod tests {
         assert_eq!(solve_with_dist(&prepare(EXAMPLE_INPUT), 10), 1030);
         assert_eq!(solve_with_dist(&prepare(EXAMPLE_INPUT), 100), 8410);
     }
+
+    /// Walks from every galaxy to every other one, paying for each crossed row and column.
+    fn reference(input: &str, dist: usize) -> usize {
+        let grid = input.lines().map(str::as_bytes).collect::<Vec<_>>();
+        let empty_rows = grid
+            .iter()
+            .map(|row| !row.contains(&b'#'))
+            .collect::<Vec<_>>();
+        let empty_columns = (0..grid[0].len())
+            .map(|x| grid.iter().all(|row| row[x] != b'#'))
+            .collect::<Vec<_>>();
+        let cost = |empty: &[bool], a: usize, b: usize| -> usize {
+            (a.min(b)..a.max(b))
+                .map(|i| if empty[i] { dist } else { 1 })
+                .sum()
+        };
+        let galaxies = prepare(input);
+        let mut total = 0;
+        for (i, a) in galaxies.iter().enumerate() {
+            for b in &galaxies[i + 1..] {
+                total += cost(&empty_rows, a.0, b.0) + cost(&empty_columns, a.1, b.1);
+            }
+        }
+        total
+    }
+    #[test]
+    fn matches_reference() {
+        assert_matches_reference(
+            generate::day11::generate,
+            20,
+            &[0.1],
+            |input| {
+                let prepared = prepare(input);
+                (solve_part1(&prepared), solve_part2(&prepared))
+            },
+            |input| (reference(input, 2), reference(input, 1_000_000)),
+        );
+    }
 }
@@ -1,4 +1,5 @@
 use crate::day::day12::Condition::{Broken, Operational, Unknown};
+use crate::error::Error;
 use crate::solution::Solution;
 use bstr::ByteSlice;
 use winnow::ascii::dec_uint;
@@ -14,11 +15,12 @@ pub enum Condition {
 
 type PreparedInput = Vec<(Vec<Condition>, Vec<u8>)>;
 
-pub fn prepare(input: &str) -> PreparedInput {
+pub fn try_prepare(input: &str) -> Result<PreparedInput, Error> {
     input
         .as_bytes()
         .lines()
-        .map(|line| {
+        .enumerate()
+        .map(|(i, line)| {
             separated_pair(
                 repeat(
                     1..,
@@ -32,33 +34,41 @@ pub fn prepare(input: &str) -> PreparedInput {
                 separated(1.., dec_uint::<_, u8, ()>, ','),
             )
             .parse(line)
-            .unwrap()
+            .map_err(|err| Error::Parse {
+                line: i + 1,
+                column: err.offset() + 1,
+            })
         })
         .collect()
 }
 
-fn calc_arrangement_count(line: &[Condition], expected: &[u8]) -> usize {
+pub fn prepare(input: &str) -> PreparedInput {
+    try_prepare(input).unwrap()
+}
+
+/// Number of arrangements, or `None` if it does not fit in a `usize`.
+fn calc_arrangement_count(line: &[Condition], expected: &[u8]) -> Option<usize> {
     fn inner<'a>(
         cache: &mut Vec<Vec<usize>>,
         line: &'a [Condition],
         expected: &'a [u8],
         expected_total: usize,
-    ) -> usize {
+    ) -> Option<usize> {
         if expected.is_empty() {
             // No more broken springs expected.
             if !line.contains(&Broken) {
-                return 1;
+                return Some(1);
             }
-            return 0;
+            return Some(0);
         }
         let nonop_index = line.iter().position(|c| *c != Operational);
         if nonop_index.is_none() {
             // No more broken springs possible, but it is expected.
-            return 0;
+            return Some(0);
         }
         let cached = cache[expected.len() - 1][line.len() - 1];
         if cached != 0 {
-            return cached - 1;
+            return Some(cached - 1);
         }
         let line = &line[nonop_index.unwrap()..];
         let next_length = expected[0] as usize;
@@ -68,7 +78,7 @@ fn calc_arrangement_count(line: &[Condition], expected: &[u8]) -> usize {
                 .all(|c| matches!(*c, Broken | Unknown))
             && line
                 .get(next_length)
-                .map_or(true, |c| matches!(*c, Operational | Unknown));
+                .is_none_or(|c| matches!(*c, Operational | Unknown));
 
         let mut sum = 0;
         if possible {
@@ -77,36 +87,44 @@ fn calc_arrangement_count(line: &[Condition], expected: &[u8]) -> usize {
                 &line[(next_length + 1).min(line.len())..],
                 &expected[1..],
                 expected_total - expected[0] as usize,
-            );
+            )?;
         }
         if line[0] == Unknown {
             let line = &line[1..];
             if line.len() >= expected_total + expected.len() - 1 {
-                sum += inner(cache, line, expected, expected_total);
+                sum = sum.checked_add(inner(cache, line, expected, expected_total)?)?;
             }
         }
-        cache[expected.len() - 1][line.len() - 1] = sum + 1;
-        sum
+        cache[expected.len() - 1][line.len() - 1] = sum.checked_add(1)?;
+        Some(sum)
     }
     inner(
         &mut vec![vec![0; line.len()]; expected.len() + 1],
         line,
         expected,
-        expected.iter().sum::<u8>() as usize,
+        expected.iter().map(|&length| length as usize).sum(),
     )
 }
 
-pub fn solve_part1(input: &PreparedInput) -> usize {
+const TOO_MANY_ARRANGEMENTS: Error = Error::Invalid("too many arrangements");
+
+pub fn try_solve_part1(input: &PreparedInput) -> Result<usize, Error> {
     input
         .iter()
-        .map(|(line, expected)| calc_arrangement_count(line, expected))
-        .sum()
+        .try_fold(0usize, |sum, (line, expected)| {
+            sum.checked_add(calc_arrangement_count(line, expected)?)
+        })
+        .ok_or(TOO_MANY_ARRANGEMENTS)
 }
 
-pub fn solve_part2(input: &PreparedInput) -> usize {
+pub fn solve_part1(input: &PreparedInput) -> usize {
+    try_solve_part1(input).unwrap()
+}
+
+pub fn try_solve_part2(input: &PreparedInput) -> Result<usize, Error> {
     input
         .iter()
-        .map(|(line, expected)| {
+        .try_fold(0usize, |sum, (line, expected)| {
             let q = vec![Unknown];
             let line = [line, &q, line, &q, line, &q, line, &q, line]
                 .into_iter()
@@ -118,19 +136,31 @@ pub fn solve_part2(input: &PreparedInput) -> usize {
                 .flatten()
                 .copied()
                 .collect::<Vec<_>>();
-            calc_arrangement_count(&line, &expected)
+            sum.checked_add(calc_arrangement_count(&line, &expected)?)
         })
-        .sum()
+        .ok_or(TOO_MANY_ARRANGEMENTS)
+}
+
+pub fn solve_part2(input: &PreparedInput) -> usize {
+    try_solve_part2(input).unwrap()
+}
+
+pub fn try_solve(input: &str) -> Result<(Solution, Solution), Error> {
+    let input = try_prepare(input)?;
+    Ok((
+        try_solve_part1(&input)?.into(),
+        try_solve_part2(&input)?.into(),
+    ))
 }
 
 pub fn solve(input: &str) -> (Solution, Solution) {
-    let input = prepare(input);
-    (solve_part1(&input).into(), solve_part2(&input).into())
+    try_solve(input).unwrap()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::generate::{self, assert_matches_reference};
 
     const EXAMPLE_INPUT: &str = "???.### 1,1,3
 .??..??...?##. 1,1,3
@@ -150,4 +180,93 @@ mod tests {
     fn example_part2() {
         assert_eq!(solve_part2(&prepare(EXAMPLE_INPUT)), 525152);
     }
+
+    fn groups(springs: &[u8]) -> Vec<u8> {
+        springs
+            .split(|&spring| spring != b'#')
+            .filter(|group| !group.is_empty())
+            .map(|group| group.len() as u8)
+            .collect()
+    }
+    /// Tries every assignment of the unknown springs.
+    fn brute_force(springs: &[u8], expected: &[u8]) -> usize {
+        let unknown = (0..springs.len())
+            .filter(|&i| springs[i] == b'?')
+            .collect::<Vec<_>>();
+        (0..1u32 << unknown.len())
+            .filter(|assignment| {
+                let mut springs = springs.to_vec();
+                for (bit, &i) in unknown.iter().enumerate() {
+                    springs[i] = if assignment & (1 << bit) != 0 {
+                        b'#'
+                    } else {
+                        b'.'
+                    };
+                }
+                groups(&springs) == expected
+            })
+            .count()
+    }
+    /// Places the groups one at a time, remembering the count for each remaining suffix.
+    fn count_placements(
+        springs: &[u8],
+        expected: &[u8],
+        memo: &mut std::collections::HashMap<(usize, usize), usize>,
+    ) -> usize {
+        let Some((&first, rest)) = expected.split_first() else {
+            return if springs.contains(&b'#') { 0 } else { 1 };
+        };
+        let key = (springs.len(), expected.len());
+        if let Some(&count) = memo.get(&key) {
+            return count;
+        }
+        let first = first as usize;
+        let mut count = 0;
+        for start in 0..springs.len() {
+            if start + first > springs.len() || springs[..start].contains(&b'#') {
+                break;
+            }
+            let fits = !springs[start..start + first].contains(&b'.')
+                && springs.get(start + first) != Some(&b'#');
+            if fits {
+                let remaining = &springs[(start + first + 1).min(springs.len())..];
+                count += count_placements(remaining, rest, memo);
+            }
+        }
+        memo.insert(key, count);
+        count
+    }
+    fn reference(input: &str) -> (usize, usize) {
+        input.lines().fold((0, 0), |(part1, part2), line| {
+            let (springs, expected) = line.split_once(' ').unwrap();
+            let expected = expected
+                .split(',')
+                .map(|num| num.parse().unwrap())
+                .collect::<Vec<u8>>();
+            let unfolded_springs = [springs; 5].join("?");
+            let unfolded_expected = expected.repeat(5);
+            (
+                part1 + brute_force(springs.as_bytes(), &expected),
+                part2
+                    + count_placements(
+                        unfolded_springs.as_bytes(),
+                        &unfolded_expected,
+                        &mut Default::default(),
+                    ),
+            )
+        })
+    }
+    #[test]
+    fn matches_reference() {
+        assert_matches_reference(
+            generate::day12::generate,
+            10,
+            &[0.02],
+            |input| {
+                let prepared = prepare(input);
+                (solve_part1(&prepared), solve_part2(&prepared))
+            },
+            reference,
+        );
+    }
 }
@@ -1,30 +1,36 @@
+use crate::error::Error;
 use crate::solution::Solution;
 use crate::util::grid::Grid;
 
 type PreparedInput = Vec<Grid<bool>>;
 
 pub fn prepare(input: &str) -> PreparedInput {
+    try_prepare(input).unwrap()
+}
+
+pub fn try_prepare(input: &str) -> Result<PreparedInput, Error> {
+    let mut first_line = 0;
     input
         .split("\n\n")
         .map(|block| {
-            Grid::from_rows(block.lines().map(|line| {
-                line.chars().map(|c| match c {
-                    '.' => false,
-                    '#' => true,
-                    _ => panic!(),
-                })
-            }))
+            // Errors are reported relative to the block, shift them to the line in the input.
+            let grid = Grid::parse(block).map_err(|err| match Error::from(err) {
+                Error::Parse { line, column } => Error::Parse {
+                    line: first_line + line,
+                    column,
+                },
+                err => err,
+            })?;
+            first_line += grid.dimensions.height() + 1;
+            Ok(grid)
         })
         .collect()
 }
 
-fn find_mirror_index(grid: &Grid<bool>) -> Option<usize> {
-    for i in 1..grid.dimensions.0 {
-        let valid = (0..i).rev().zip(i..grid.dimensions.0).all(|(a, b)| {
-            let a = grid.get_row(a);
-            let b = grid.get_row(b);
-            a == b
-        });
+/// Finds a mirror between two of `count` lines, which are rows or columns compared by `equal`.
+fn find_mirror_index(count: usize, equal: impl Fn(usize, usize) -> bool) -> Option<usize> {
+    for i in 1..count {
+        let valid = (0..i).rev().zip(i..count).all(|(a, b)| equal(a, b));
         if valid {
             return Some(i);
         }
@@ -32,14 +38,16 @@ fn find_mirror_index(grid: &Grid<bool>) -> Option<usize> {
     None
 }
 
-fn find_mirror_index_with_single_allowed_error(grid: &Grid<bool>) -> Option<usize> {
-    for i in 1..grid.dimensions.0 {
+fn find_mirror_index_with_single_allowed_error(
+    count: usize,
+    mismatches: impl Fn(usize, usize) -> usize,
+) -> Option<usize> {
+    for i in 1..count {
         let mut error_encountered = false;
-        let valid = (0..i).rev().zip(i..grid.dimensions.0).all(|(a, b)| {
-            let a = grid.get_row(a);
-            let b = grid.get_row(b);
-            let error_count = a.iter().zip(b.iter()).filter(|(a, b)| a != b).count();
-            match error_count {
+        let valid = (0..i)
+            .rev()
+            .zip(i..count)
+            .all(|(a, b)| match mismatches(a, b) {
                 0 => true,
                 1 => {
                     if error_encountered {
@@ -50,8 +58,7 @@ fn find_mirror_index_with_single_allowed_error(grid: &Grid<bool>) -> Option<usiz
                     }
                 }
                 _ => false,
-            }
-        });
+            });
         if valid && error_encountered {
             return Some(i);
         }
@@ -59,46 +66,69 @@ fn find_mirror_index_with_single_allowed_error(grid: &Grid<bool>) -> Option<usiz
     None
 }
 
-pub fn solve_part1(input: &PreparedInput) -> usize {
+const NO_MIRROR: Error = Error::Invalid("every pattern needs a mirror");
+
+pub fn try_solve_part1(input: &PreparedInput) -> Result<usize, Error> {
     input
         .iter()
         .map(|grid| {
-            if let Some(j) = find_mirror_index(grid) {
-                return j * 100;
+            let (height, width) = (grid.dimensions.height(), grid.dimensions.width());
+            if let Some(j) = find_mirror_index(height, |a, b| grid.get_row(a) == grid.get_row(b)) {
+                return Ok(j * 100);
             }
-            let transposed = grid.transposed();
-            if let Some(i) = find_mirror_index(&transposed) {
-                return i;
+            if let Some(i) = find_mirror_index(width, |a, b| grid.columns_equal(a, b)) {
+                return Ok(i);
             }
-            panic!("{}", grid)
+            Err(NO_MIRROR)
         })
         .sum()
 }
 
-pub fn solve_part2(input: &PreparedInput) -> usize {
+pub fn solve_part1(input: &PreparedInput) -> usize {
+    try_solve_part1(input).unwrap()
+}
+
+pub fn try_solve_part2(input: &PreparedInput) -> Result<usize, Error> {
     input
         .iter()
         .map(|grid| {
-            if let Some(j) = find_mirror_index_with_single_allowed_error(grid) {
-                return j * 100;
+            let (height, width) = (grid.dimensions.height(), grid.dimensions.width());
+            let row_mismatches = |a, b| {
+                let (a, b) = (grid.get_row(a), grid.get_row(b));
+                a.iter().zip(b).filter(|(a, b)| a != b).count()
+            };
+            if let Some(j) = find_mirror_index_with_single_allowed_error(height, row_mismatches) {
+                return Ok(j * 100);
             }
-            let transposed = grid.transposed();
-            if let Some(i) = find_mirror_index_with_single_allowed_error(&transposed) {
-                return i;
+            let column_mismatches = |a, b| grid.column_mismatches(a, b);
+            if let Some(i) = find_mirror_index_with_single_allowed_error(width, column_mismatches) {
+                return Ok(i);
             }
-            panic!("{}", grid)
+            Err(NO_MIRROR)
         })
         .sum()
 }
 
+pub fn solve_part2(input: &PreparedInput) -> usize {
+    try_solve_part2(input).unwrap()
+}
+
+pub fn try_solve(input: &str) -> Result<(Solution, Solution), Error> {
+    let input = try_prepare(input)?;
+    Ok((
+        try_solve_part1(&input)?.into(),
+        try_solve_part2(&input)?.into(),
+    ))
+}
+
 pub fn solve(input: &str) -> (Solution, Solution) {
-    let input = prepare(input);
-    (solve_part1(&input).into(), solve_part2(&input).into())
+    try_solve(input).unwrap()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::generate::{self, assert_matches_reference};
 
     const EXAMPLE_INPUT: &str = "#.##..##.
 ..#.##.#.
@@ -120,6 +150,15 @@ mod tests {
         assert_eq!(prepare(EXAMPLE_INPUT).len(), 2);
     }
     #[test]
+    fn invalid_pattern() {
+        assert_eq!(
+            try_prepare("#.\n.#\n\n#.\n.x").err(),
+            Some(Error::Parse { line: 5, column: 2 })
+        );
+        assert!(try_prepare("#.\n\n\n#.").is_err());
+        assert!(try_prepare("#.\n\n").is_err());
+    }
+    #[test]
     fn example_part1() {
         assert_eq!(solve_part1(&prepare(EXAMPLE_INPUT)), 405);
     }
@@ -127,4 +166,62 @@ mod tests {
     fn example_part2() {
         assert_eq!(solve_part2(&prepare(EXAMPLE_INPUT)), 400);
     }
+
+    /// Every line reflecting the pattern, scored as in the answer.
+    fn reflections(pattern: &[Vec<u8>]) -> Vec<usize> {
+        let (height, width) = (pattern.len(), pattern[0].len());
+        let horizontal = (1..height)
+            .filter(|&line| {
+                (0..line)
+                    .rev()
+                    .zip(line..height)
+                    .all(|(a, b)| pattern[a] == pattern[b])
+            })
+            .map(|line| 100 * line);
+        let vertical = (1..width).filter(|&line| {
+            (0..line)
+                .rev()
+                .zip(line..width)
+                .all(|(a, b)| pattern.iter().all(|row| row[a] == row[b]))
+        });
+        horizontal.chain(vertical).collect()
+    }
+    /// Fixes every possible smudge in turn until a different reflection shows up.
+    fn reference(input: &str) -> (usize, usize) {
+        input.split("\n\n").fold((0, 0), |(part1, part2), block| {
+            let mut pattern = block
+                .lines()
+                .map(|line| line.as_bytes().to_vec())
+                .collect::<Vec<_>>();
+            let original = reflections(&pattern)[0];
+            let mut smudged = None;
+            'search: for y in 0..pattern.len() {
+                for x in 0..pattern[0].len() {
+                    let cell = pattern[y][x];
+                    pattern[y][x] = if cell == b'#' { b'.' } else { b'#' };
+                    smudged = reflections(&pattern)
+                        .into_iter()
+                        .find(|&reflection| reflection != original);
+                    pattern[y][x] = cell;
+                    if smudged.is_some() {
+                        break 'search;
+                    }
+                }
+            }
+            (part1 + original, part2 + smudged.unwrap())
+        })
+    }
+    #[test]
+    fn matches_reference() {
+        assert_matches_reference(
+            generate::day13::generate,
+            20,
+            &[0.1],
+            |input| {
+                let prepared = prepare(input);
+                (solve_part1(&prepared), solve_part2(&prepared))
+            },
+            reference,
+        );
+    }
 }
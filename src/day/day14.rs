@@ -1,8 +1,12 @@
 use crate::day::day14::Element::{CubeShapedRock, Empty, RoundedRock};
+use crate::error::Error;
+use crate::frames::record_frame;
 use crate::solution::Solution;
-use crate::util::grid::{CellDisplay, Grid};
-use crate::util::position::{Direction, Position};
-use rustc_hash::FxHashMap;
+use crate::util::bitgrid::BitGrid;
+use crate::util::grid::{CellDisplay, CellParse, Grid, GridView};
+use crate::util::image::CellColor;
+use crate::util::position::Position;
+use crate::util::solver::find_cycle_hashed;
 use std::fmt::{Formatter, Write};
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
@@ -21,56 +25,58 @@ impl CellDisplay for Element {
     }
 }
 
+impl CellColor for Element {
+    fn color(&self) -> [u8; 3] {
+        match self {
+            Empty => [0, 0, 0],
+            RoundedRock => [230, 200, 60],
+            CubeShapedRock => [110, 110, 110],
+        }
+    }
+}
+
+impl CellParse for Element {
+    fn parse(c: char) -> Option<Self> {
+        match c {
+            '.' => Some(Empty),
+            'O' => Some(RoundedRock),
+            '#' => Some(CubeShapedRock),
+            _ => None,
+        }
+    }
+}
+
 type PreparedInput = Grid<Element>;
 
 pub fn prepare(input: &str) -> PreparedInput {
-    Grid::from_rows(input.lines().map(|line| {
-        line.chars().map(|c| match c {
-            '.' => Empty,
-            'O' => RoundedRock,
-            '#' => CubeShapedRock,
-            _ => panic!(),
-        })
-    }))
+    try_prepare(input).unwrap()
 }
 
-fn tilt(grid: &mut Grid<Element>, direction: Direction) {
-    let position_iterator = grid.positions();
-    let dimensions = grid.dimensions.into();
-    let func = |pos: Position| {
-        let value = grid.get(&pos);
-        if *value == RoundedRock {
-            let mut current_pos = pos;
-            loop {
-                let next_pos =
-                    current_pos
-                        .checked_moved(&dimensions, &direction)
-                        .and_then(|next_pos| {
-                            if matches!(grid.get(&next_pos), Empty) {
-                                Some(next_pos)
-                            } else {
-                                None
-                            }
-                        });
-                if next_pos.is_none() {
-                    grid.set(&pos, Empty);
-                    grid.set(&current_pos, RoundedRock);
-                    break;
-                }
-                current_pos = next_pos.unwrap();
+pub fn try_prepare(input: &str) -> Result<PreparedInput, Error> {
+    Ok(Grid::parse(input)?)
+}
+
+/// Rolls the rounded rocks north until they hit the edge, a cube shaped rock or another rock.
+fn tilt_north(grid: &mut Grid<Element>) {
+    // Per column, the row the next rounded rock comes to rest in.
+    let mut free_rows = vec![0; grid.dimensions.width()];
+    for pos in grid.dimensions.positions() {
+        match grid.get(&pos) {
+            CubeShapedRock => free_rows[pos.x()] = pos.y() + 1,
+            RoundedRock => {
+                grid.set(&pos, Empty);
+                grid.set(&Position(free_rows[pos.x()], pos.x()), RoundedRock);
+                free_rows[pos.x()] += 1;
             }
+            Empty => {}
         }
-    };
-    match direction {
-        Direction::Up | Direction::Left => position_iterator.for_each(func),
-        Direction::Down | Direction::Right => position_iterator.rev().for_each(func),
-    };
+    }
 }
 
 fn calc_total_load(grid: &Grid<Element>) -> usize {
     grid.iter()
         .map(|(pos, value)| match value {
-            RoundedRock => grid.dimensions.1 - pos.y(),
+            RoundedRock => grid.dimensions.height() - pos.y(),
             _ => 0,
         })
         .sum()
@@ -78,42 +84,54 @@ fn calc_total_load(grid: &Grid<Element>) -> usize {
 
 pub fn solve_part1(input: &PreparedInput) -> usize {
     let mut grid = input.clone();
-    tilt(&mut grid, Direction::Up);
+    tilt_north(&mut grid);
     calc_total_load(&grid)
 }
 
-pub fn solve_part2(input: &PreparedInput) -> usize {
-    let mut grid = input.clone();
+fn spin_cycle(grid: &Grid<Element>) -> Grid<Element> {
+    let mut grid = grid.clone();
+    // West, south and east each end up north after one more clockwise turn, and the fourth turn
+    // brings the grid back the way it was.
+    for _ in 0..4 {
+        tilt_north(&mut grid);
+        grid = grid.rotated90();
+    }
+    record_frame!("day14", &grid);
+    grid
+}
 
-    let mut map = FxHashMap::default();
-    let mut total_loads = vec![];
-
-    let cycles = 1000000000;
-    for i in 0..cycles {
-        tilt(&mut grid, Direction::Up);
-        tilt(&mut grid, Direction::Left);
-        tilt(&mut grid, Direction::Down);
-        tilt(&mut grid, Direction::Right);
-        total_loads.push(calc_total_load(&grid));
-
-        if let Some(existing) = map.insert(grid.clone(), i) {
-            let loop_length = i - existing;
-            let remaining_loops = cycles - existing - 1;
-            let cycles_after_loop_reached = remaining_loops % loop_length;
-            return total_loads[existing + cycles_after_loop_reached];
+/// The rounded rocks are all that changes between cycles.
+fn rounded_rocks(grid: &Grid<Element>) -> BitGrid {
+    let mut rocks = BitGrid::from_dimensions(grid.dimensions);
+    for (pos, value) in grid.iter() {
+        if *value == RoundedRock {
+            rocks.insert(&pos);
         }
     }
-    panic!()
+    rocks
+}
+
+pub fn solve_part2(input: &PreparedInput) -> usize {
+    let (cycle, rocks) = find_cycle_hashed(input.clone(), spin_cycle, rounded_rocks);
+    let rocks = &rocks[cycle.equivalent_step(1_000_000_000)];
+    let height = rocks.dimensions.height();
+    (0..height).map(|y| rocks.count_row(y) * (height - y)).sum()
+}
+
+pub fn try_solve(input: &str) -> Result<(Solution, Solution), Error> {
+    let input = try_prepare(input)?;
+    Ok((solve_part1(&input).into(), solve_part2(&input).into()))
 }
 
 pub fn solve(input: &str) -> (Solution, Solution) {
-    let input = prepare(input);
-    (solve_part1(&input).into(), solve_part2(&input).into())
+    try_solve(input).unwrap()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::generate::{self, assert_matches_reference};
+    use crate::util::position::Dimensions;
 
     const EXAMPLE_INPUT: &str = "O....#....
 O.OO#....#
@@ -127,7 +145,10 @@ O.#..O.#.#
 #OO..#....";
     #[test]
     fn example_prepare() {
-        assert_eq!(prepare(EXAMPLE_INPUT).dimensions, (10, 10));
+        assert_eq!(
+            prepare(EXAMPLE_INPUT).dimensions,
+            Dimensions::from_height_width(10, 10)
+        );
     }
     #[test]
     fn example_part1() {
@@ -137,4 +158,70 @@ O.#..O.#.#
     fn example_part2() {
         assert_eq!(solve_part2(&prepare(EXAMPLE_INPUT)), 64);
     }
+
+    /// Moves rounded rocks a single tile north while any of them can still move.
+    fn roll_north(rows: &mut [Vec<u8>]) {
+        let mut moved = true;
+        while moved {
+            moved = false;
+            for y in 1..rows.len() {
+                for x in 0..rows[y].len() {
+                    if rows[y][x] == b'O' && rows[y - 1][x] == b'.' {
+                        rows[y - 1][x] = b'O';
+                        rows[y][x] = b'.';
+                        moved = true;
+                    }
+                }
+            }
+        }
+    }
+    /// Rotates clockwise, so that rolling north afterwards rolls towards the next direction.
+    fn rotate(rows: &[Vec<u8>]) -> Vec<Vec<u8>> {
+        (0..rows[0].len())
+            .map(|x| rows.iter().rev().map(|row| row[x]).collect())
+            .collect()
+    }
+    fn load(rows: &[Vec<u8>]) -> usize {
+        rows.iter()
+            .enumerate()
+            .map(|(y, row)| (rows.len() - y) * row.iter().filter(|&&c| c == b'O').count())
+            .sum()
+    }
+    fn reference(input: &str) -> (usize, usize) {
+        let mut rows = input
+            .lines()
+            .map(|line| line.as_bytes().to_vec())
+            .collect::<Vec<_>>();
+        let mut tilted = rows.clone();
+        roll_north(&mut tilted);
+        let part1 = load(&tilted);
+
+        let cycles = 1_000_000_000;
+        let mut history = vec![rows.clone()];
+        loop {
+            for _ in 0..4 {
+                roll_north(&mut rows);
+                rows = rotate(&rows);
+            }
+            if let Some(start) = history.iter().position(|previous| *previous == rows) {
+                let period = history.len() - start;
+                let part2 = load(&history[start + (cycles - start) % period]);
+                return (part1, part2);
+            }
+            history.push(rows.clone());
+        }
+    }
+    #[test]
+    fn matches_reference() {
+        assert_matches_reference(
+            generate::day14::generate,
+            10,
+            &[0.05],
+            |input| {
+                let prepared = prepare(input);
+                (solve_part1(&prepared), solve_part2(&prepared))
+            },
+            reference,
+        );
+    }
 }
@@ -1,11 +1,30 @@
+use crate::error::Error;
 use crate::solution::Solution;
 
 type PreparedInput<'a> = Vec<&'a [u8]>;
 
-pub fn prepare(input: &str) -> PreparedInput {
+pub fn prepare(input: &str) -> PreparedInput<'_> {
     input.split(',').map(|line| line.as_bytes()).collect()
 }
 
+pub fn try_prepare(input: &str) -> Result<PreparedInput<'_>, Error> {
+    let prepared = prepare(input);
+    let mut offset = 0;
+    for steps in &prepared {
+        // Either removes a lens, `label-`, or inserts one with a single digit focal length, `label=4`.
+        let valid = match steps {
+            [.., b'-'] => true,
+            [.., b'=', focal_length] => focal_length.is_ascii_digit(),
+            _ => false,
+        };
+        if !valid {
+            return Err(Error::parse_at(input.as_bytes(), offset + steps.len()));
+        }
+        offset += steps.len() + 1;
+    }
+    Ok(prepared)
+}
+
 fn hash(bytes: &[u8]) -> u32 {
     bytes.iter().fold(0, |mut current, c| {
         current += *c as u32;
@@ -51,14 +70,19 @@ pub fn solve_part2(input: &PreparedInput) -> usize {
         .sum()
 }
 
+pub fn try_solve(input: &str) -> Result<(Solution, Solution), Error> {
+    let input = try_prepare(input)?;
+    Ok((solve_part1(&input).into(), solve_part2(&input).into()))
+}
+
 pub fn solve(input: &str) -> (Solution, Solution) {
-    let input = prepare(input);
-    (solve_part1(&input).into(), solve_part2(&input).into())
+    try_solve(input).unwrap()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::generate::{self, assert_matches_reference};
 
     const EXAMPLE_INPUT: &str = "rn=1,cm-,qp=3,cm=2,qp-,pc=4,ot=9,ab=5,pc-,pc=6,ot=7";
     #[test]
@@ -73,4 +97,50 @@ mod tests {
     fn example_part2() {
         assert_eq!(solve_part2(&prepare(EXAMPLE_INPUT)), 145);
     }
+
+    fn reference(input: &str) -> (u32, usize) {
+        let hash = |text: &str| {
+            text.bytes()
+                .fold(0u32, |current, c| (current + c as u32) * 17 % 256)
+        };
+        let part1 = input.split(',').map(hash).sum();
+
+        let mut boxes: Vec<Vec<(&str, usize)>> = vec![vec![]; 256];
+        for step in input.split(',') {
+            if let Some(label) = step.strip_suffix('-') {
+                let lenses = &mut boxes[hash(label) as usize];
+                if let Some(i) = lenses.iter().position(|lens| lens.0 == label) {
+                    lenses.remove(i);
+                }
+            } else {
+                let (label, focal_length) = step.split_once('=').unwrap();
+                let focal_length = focal_length.parse().unwrap();
+                let lenses = &mut boxes[hash(label) as usize];
+                match lenses.iter().position(|lens| lens.0 == label) {
+                    Some(i) => lenses[i].1 = focal_length,
+                    None => lenses.push((label, focal_length)),
+                }
+            }
+        }
+        let mut part2 = 0;
+        for (box_number, lenses) in boxes.iter().enumerate() {
+            for (slot, (_, focal_length)) in lenses.iter().enumerate() {
+                part2 += (box_number + 1) * (slot + 1) * focal_length;
+            }
+        }
+        (part1, part2)
+    }
+    #[test]
+    fn matches_reference() {
+        assert_matches_reference(
+            generate::day15::generate,
+            20,
+            &[0.1],
+            |input| {
+                let prepared = prepare(input);
+                (solve_part1(&prepared), solve_part2(&prepared))
+            },
+            reference,
+        );
+    }
 }
@@ -1,12 +1,13 @@
 use crate::day::day16::Element::{
     Empty, MirrorDown, MirrorUp, SplitterHorizontal, SplitterVertical,
 };
+use crate::error::Error;
+use crate::frames::record_frame;
 use crate::solution::Solution;
-use crate::util::grid::Grid;
+use crate::util::bitgrid::BitGrid;
+use crate::util::grid::{check_rows, BackedGrid};
 use crate::util::position::{Direction, Position, RotationalDirection};
 use crate::util::solver::solve_fn_push;
-use rustc_hash::FxHashSet;
-use std::usize;
 
 pub enum Element {
     Empty,
@@ -16,19 +17,28 @@ pub enum Element {
     SplitterHorizontal,
 }
 
-type PreparedInput = Grid<Element>;
-
-pub fn prepare(input: &str) -> PreparedInput {
-    Grid::from_rows(input.lines().map(|line| {
-        line.chars().map(|c| match c {
-            '.' => Empty,
-            '\\' => MirrorDown,
-            '/' => MirrorUp,
-            '|' => SplitterVertical,
-            '-' => SplitterHorizontal,
+impl From<&u8> for Element {
+    fn from(value: &u8) -> Self {
+        match *value {
+            b'.' => Empty,
+            b'\\' => MirrorDown,
+            b'/' => MirrorUp,
+            b'|' => SplitterVertical,
+            b'-' => SplitterHorizontal,
             _ => panic!(),
-        })
-    }))
+        }
+    }
+}
+
+type PreparedInput<'a> = BackedGrid<'a, u8>;
+
+pub fn prepare(input: &str) -> PreparedInput<'_> {
+    BackedGrid::from_data_and_row_separator(input.as_bytes(), b'\n')
+}
+
+pub fn try_prepare(input: &str) -> Result<PreparedInput<'_>, Error> {
+    check_rows(input.as_bytes(), |c| b".\\/|-".contains(&c))?;
+    Ok(prepare(input))
 }
 
 fn calc_energized_count(
@@ -36,26 +46,24 @@ fn calc_energized_count(
     direction: Direction,
     initial_position: Position,
 ) -> usize {
-    let mut all_states = FxHashSet::<u16>::default();
-    let mut energized = FxHashSet::<u16>::default();
+    // One grid per direction the beam can pass a tile in.
+    let mut all_states = [(); 4].map(|_| BitGrid::from_dimensions(grid.dimensions));
+    let mut energized = BitGrid::from_dimensions(grid.dimensions);
 
-    // Assert that positions would take up to 7 bits.
-    assert!(grid.dimensions.0 < 128);
-    assert!(grid.dimensions.1 < 128);
-
-    let dimensions = grid.dimensions.into();
+    let dimensions = grid.dimensions;
 
     solve_fn_push(
         |stack, (dir, pos)| {
-            energized.insert((pos.0 << 8) as u16 + pos.1 as u16);
-            if !all_states.insert((pos.0 << 9) as u16 + (pos.1 << 2) as u16 + *dir as u8 as u16) {
+            energized.insert(pos);
+            record_frame!("day16", &energized);
+            if !all_states[*dir as usize].insert(pos) {
                 return;
             }
 
             let mut arr = [Direction::Up; 2];
             let mut slice = &mut arr[..];
 
-            match (grid.get(pos), dir) {
+            match (grid.get_as::<Element>(pos), dir) {
                 (Empty, _)
                 | (SplitterHorizontal, Direction::Left | Direction::Right)
                 | (SplitterVertical, Direction::Up | Direction::Down) => {
@@ -101,7 +109,7 @@ fn calc_energized_count(
         vec![(direction, initial_position)],
     );
 
-    energized.len()
+    energized.count()
 }
 
 pub fn solve_part1(grid: &PreparedInput) -> usize {
@@ -110,7 +118,7 @@ pub fn solve_part1(grid: &PreparedInput) -> usize {
 
 pub fn solve_part2(grid: &PreparedInput) -> usize {
     let mut max = 0;
-    for i in 0..grid.dimensions.1 {
+    for i in 0..grid.dimensions.width() {
         max = max.max(calc_energized_count(
             grid,
             Direction::Down,
@@ -119,10 +127,10 @@ pub fn solve_part2(grid: &PreparedInput) -> usize {
         max = max.max(calc_energized_count(
             grid,
             Direction::Up,
-            Position::from_yx(grid.dimensions.0 - 1, i),
+            Position::from_yx(grid.dimensions.height() - 1, i),
         ));
     }
-    for j in 0..grid.dimensions.0 {
+    for j in 0..grid.dimensions.height() {
         max = max.max(calc_energized_count(
             grid,
             Direction::Right,
@@ -131,21 +139,27 @@ pub fn solve_part2(grid: &PreparedInput) -> usize {
         max = max.max(calc_energized_count(
             grid,
             Direction::Left,
-            Position::from_yx(j, grid.dimensions.1 - 1),
+            Position::from_yx(j, grid.dimensions.width() - 1),
         ));
     }
 
     max
 }
 
+pub fn try_solve(input: &str) -> Result<(Solution, Solution), Error> {
+    let input = try_prepare(input)?;
+    Ok((solve_part1(&input).into(), solve_part2(&input).into()))
+}
+
 pub fn solve(input: &str) -> (Solution, Solution) {
-    let input = prepare(input);
-    (solve_part1(&input).into(), solve_part2(&input).into())
+    try_solve(input).unwrap()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::generate::{self, assert_matches_reference};
+    use crate::util::position::Dimensions;
 
     const EXAMPLE_INPUT: &str = r".|...\....
 |.-.\.....
@@ -159,7 +173,10 @@ mod tests {
 ..//.|....";
     #[test]
     fn example_prepare() {
-        assert_eq!(prepare(EXAMPLE_INPUT).dimensions, (10, 10));
+        assert_eq!(
+            prepare(EXAMPLE_INPUT).dimensions,
+            Dimensions::from_height_width(10, 10)
+        );
     }
     #[test]
     fn example_part1() {
@@ -169,4 +186,54 @@ mod tests {
     fn example_part2() {
         assert_eq!(solve_part2(&prepare(EXAMPLE_INPUT)), 51);
     }
+
+    /// Follows every beam one tile at a time until no beam reaches a new tile and direction.
+    fn energized(tiles: &[&[u8]], start: (isize, isize, isize, isize)) -> usize {
+        let (height, width) = (tiles.len() as isize, tiles[0].len() as isize);
+        let mut seen = std::collections::HashSet::new();
+        let mut beams = vec![start];
+        while let Some((y, x, dy, dx)) = beams.pop() {
+            if !(0..height).contains(&y) || !(0..width).contains(&x) || !seen.insert((y, x, dy, dx))
+            {
+                continue;
+            }
+            let directions = match tiles[y as usize][x as usize] {
+                b'/' => vec![(-dx, -dy)],
+                b'\\' => vec![(dx, dy)],
+                b'|' if dx != 0 => vec![(-1, 0), (1, 0)],
+                b'-' if dy != 0 => vec![(0, -1), (0, 1)],
+                _ => vec![(dy, dx)],
+            };
+            for (dy, dx) in directions {
+                beams.push((y + dy, x + dx, dy, dx));
+            }
+        }
+        seen.iter()
+            .map(|&(y, x, _, _)| (y, x))
+            .collect::<std::collections::HashSet<_>>()
+            .len()
+    }
+    fn reference(input: &str) -> (usize, usize) {
+        let tiles = input.lines().map(str::as_bytes).collect::<Vec<_>>();
+        let (height, width) = (tiles.len() as isize, tiles[0].len() as isize);
+        let part1 = energized(&tiles, (0, 0, 0, 1));
+        let starts = (0..height)
+            .flat_map(|y| [(y, 0, 0, 1), (y, width - 1, 0, -1)])
+            .chain((0..width).flat_map(|x| [(0, x, 1, 0), (height - 1, x, -1, 0)]));
+        let part2 = starts.map(|start| energized(&tiles, start)).max().unwrap();
+        (part1, part2)
+    }
+    #[test]
+    fn matches_reference() {
+        assert_matches_reference(
+            generate::day16::generate,
+            10,
+            &[0.05],
+            |input| {
+                let prepared = prepare(input);
+                (solve_part1(&prepared), solve_part2(&prepared))
+            },
+            reference,
+        );
+    }
 }
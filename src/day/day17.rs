@@ -1,10 +1,13 @@
+use crate::error::Error;
 use crate::solution::Solution;
-use crate::util::grid::Grid;
+use crate::util::grid::{check_rows, Grid};
 use crate::util::position::Direction::{Down, Left, Right, Up};
-use crate::util::position::{Dimensions, Direction, Position};
-use crate::util::solver::{solve_fn_priority, NodeResult};
+use crate::util::position::Position;
+use crate::util::render::{Color, Renderer};
+use crate::util::solver::{a_star, try_solve_fn_buckets, BucketQueue, NodeResult};
 use bstr::ByteSlice;
 use std::cmp::Ordering;
+use std::iter::successors;
 use std::ops::RangeInclusive;
 
 type PreparedInput = Grid<u8>;
@@ -18,123 +21,179 @@ pub fn prepare(input: &str) -> PreparedInput {
     )
 }
 
-#[derive(Debug, Eq, PartialEq)]
-struct State {
-    target: Position,
-    position: Position,
-    direction: Direction,
-    estimated_heat_loss: u32,
+pub fn try_prepare(input: &str) -> Result<PreparedInput, Error> {
+    // The heat loss estimate relies on every block losing at least one.
+    check_rows(input.as_bytes(), |c| (b'1'..=b'9').contains(&c))?;
+    Ok(prepare(input))
 }
-impl Ord for State {
-    fn cmp(&self, other: &Self) -> Ordering {
-        self.estimated_heat_loss
-            .cmp(&other.estimated_heat_loss)
-            .reverse()
-    }
-}
-impl PartialOrd for State {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
+
+/// A block the crucible stops at, and whether it got there moving horizontally, so it has to turn
+/// to move vertically next.
+pub type State = (Position, bool);
+
+/// Calls `visit` for every block the crucible can stop at next, with the heat lost on the way.
+/// It has to turn at every stop and moves `range` blocks in a straight line in between.
+#[inline]
+fn for_each_move(
+    grid: &PreparedInput,
+    range: &RangeInclusive<usize>,
+    (position, horizontal): State,
+    mut visit: impl FnMut(State, u32),
+) {
+    let directions = match horizontal {
+        true => [Down, Up],
+        false => [Right, Left],
+    };
+    let dimensions = grid.dimensions;
+    for direction in directions {
+        let mut position = position;
+        let mut heat_loss = 0;
+        for steps_moved in 1..=*range.end() {
+            position = match position.checked_moved(&dimensions, &direction) {
+                None => break,
+                Some(next) => next,
+            };
+            heat_loss += *grid.get(&position) as u32;
+            if steps_moved >= *range.start() {
+                visit((position, !horizontal), heat_loss);
+            }
+        }
     }
 }
 
-fn solve_part(grid: &PreparedInput, range: RangeInclusive<usize>) -> u32 {
-    let dimensions: Dimensions = grid.dimensions.into();
+/// The cheapest path through the blocks where the crucible stops, together with its heat loss.
+/// Used for rendering, [`solve_part`] finds the heat loss alone with a faster bucket queue. The
+/// benches compare the two.
+pub fn find_path(grid: &PreparedInput, range: RangeInclusive<usize>) -> Option<(u32, Vec<State>)> {
+    let dimensions = grid.dimensions;
+    let target = Position(dimensions.height() - 1, dimensions.width() - 1);
 
-    let mut distances = [
-        Grid::from_dimensions(grid.dimensions, u32::MAX),
-        Grid::from_dimensions(grid.dimensions, u32::MAX),
-    ];
+    a_star(
+        [(Position(0, 0), true), (Position(0, 0), false)],
+        |&state| {
+            let mut next_states = Vec::with_capacity(2 * range.end());
+            for_each_move(grid, &range, state, |next, heat_loss| {
+                next_states.push((next, heat_loss))
+            });
+            next_states
+        },
+        |(position, _)| *position == target,
+        // One number per block and axis hashes faster than the state itself.
+        |&(position, horizontal)| {
+            (position.y() * dimensions.width() + position.x()) * 2 + horizontal as usize
+        },
+        // Every block loses at least one, so this never overestimates.
+        |(position, _)| position.manhattan_distance(&target) as u32,
+    )
+}
 
+/// The lowest heat loss on the way from the top left to the bottom right block.
+pub fn solve_part(grid: &PreparedInput, range: RangeInclusive<usize>) -> Option<u32> {
+    let dimensions = grid.dimensions;
     let target = Position(dimensions.height() - 1, dimensions.width() - 1);
-    solve_fn_priority(
-        |stack, state| {
-            if state.position == state.target {
-                return NodeResult::Stop;
-            }
+    // The lowest heat loss found so far, per block and whether it was reached moving horizontally.
+    let mut heat_losses = [
+        Grid::from_dimensions(dimensions, u32::MAX),
+        Grid::from_dimensions(dimensions, u32::MAX),
+    ];
+    // Every block loses at least one, so the estimate never decreases along a path and the
+    // priorities popped from the bucket queue are monotone.
+    let estimate = |position: &Position, heat_loss: u32| {
+        heat_loss as usize + position.manhattan_distance(&target)
+    };
 
-            let mut attempt_move = |attempt_direction: Direction| {
-                let mut position = state.position;
-                let mut next_estimated_heat_loss = state.estimated_heat_loss;
-
-                let estimation_changed_by_direction = match attempt_direction {
-                    Right | Down => 0,
-                    Left | Up => 2,
-                };
-
-                for steps_moved in 1..=*range.end() {
-                    position = match position.checked_moved(&dimensions, &attempt_direction) {
-                        None => return,
-                        Some(next) => next,
-                    };
-                    next_estimated_heat_loss +=
-                        *grid.get(&position) as u32 + estimation_changed_by_direction - 1;
-
-                    if steps_moved < *range.start() {
-                        continue;
-                    }
-                    let next_state = State {
-                        target: state.target,
-                        position,
-                        direction: attempt_direction,
-                        estimated_heat_loss: next_estimated_heat_loss,
-                    };
-
-                    let entry =
-                        distances[(attempt_direction as u8 % 2) as usize].get_mut(&position);
-                    if *entry <= next_estimated_heat_loss {
-                        continue;
-                    }
-                    *entry = next_estimated_heat_loss;
-
-                    stack.push(next_state);
+    // Flat instead of a `State` with the heat loss, which keeps the queued states smaller.
+    let next = |queue: &mut BucketQueue<(Position, bool, u32)>,
+                &(position, horizontal, heat_loss): &(Position, bool, u32)| {
+        if position == target {
+            return NodeResult::Stop;
+        }
+        if heat_loss > *heat_losses[horizontal as usize].get(&position) {
+            return NodeResult::Next;
+        }
+        let best_heat_losses = &mut heat_losses[!horizontal as usize];
+        for_each_move(
+            grid,
+            &range,
+            (position, horizontal),
+            |(next, _), step_heat_loss| {
+                let next_heat_loss = heat_loss + step_heat_loss;
+                let best = best_heat_losses.get_mut(&next);
+                if next_heat_loss < *best {
+                    *best = next_heat_loss;
+                    let priority = estimate(&next, next_heat_loss);
+                    queue.push(priority, (next, !horizontal, next_heat_loss));
                 }
-            };
-
-            if state.direction != Left && state.direction != Right {
-                attempt_move(Right);
-                attempt_move(Left);
-            }
-            if state.direction != Up && state.direction != Down {
-                attempt_move(Down);
-                attempt_move(Up);
-            }
-            NodeResult::Next
-        },
-        vec![
-            State {
-                target,
-                position: Position(0, 0),
-                direction: Right,
-                estimated_heat_loss: Position(0, 0).manhattan_distance(&target) as u32,
-            },
-            State {
-                target,
-                position: Position(0, 0),
-                direction: Down,
-                estimated_heat_loss: Position(0, 0).manhattan_distance(&target) as u32,
             },
-        ],
-    )
-    .estimated_heat_loss
+        );
+        NodeResult::Next
+    };
+
+    let start = estimate(&Position(0, 0), 0);
+    let starts = vec![
+        (start, (Position(0, 0), true, 0)),
+        (start, (Position(0, 0), false, 0)),
+    ];
+    try_solve_fn_buckets(next, starts).map(|(_, _, heat_loss)| heat_loss)
+}
+
+const UNREACHABLE: Error = Error::Invalid("the crucible has to be able to reach the end");
+
+/// Draws the blocks with the path of the crucible from part 1 as arrows.
+pub fn render(input: &str) -> Result<Renderer, Error> {
+    let grid = try_prepare(input)?;
+    let (_, path) = find_path(&grid, 1..=3).ok_or(UNREACHABLE)?;
+    let mut renderer = Renderer::new(&grid, |&heat_loss| (b'0' + heat_loss) as char);
+    for stops in path.windows(2) {
+        let ((from, _), (to, _)) = (stops[0], stops[1]);
+        let (direction, glyph) = match (to.y().cmp(&from.y()), to.x().cmp(&from.x())) {
+            (Ordering::Less, _) => (Up, '^'),
+            (Ordering::Greater, _) => (Down, 'v'),
+            (_, Ordering::Greater) => (Right, '>'),
+            _ => (Left, '<'),
+        };
+        let blocks = successors(Some(from), |pos| {
+            pos.checked_moved(&grid.dimensions, &direction)
+        });
+        let moved = blocks.skip(1).take(from.manhattan_distance(&to));
+        renderer = renderer.overlay_glyph(Color::Yellow, glyph, moved);
+    }
+    Ok(renderer)
+}
+
+pub fn try_solve_part1(grid: &PreparedInput) -> Result<u32, Error> {
+    solve_part(grid, 1..=3).ok_or(UNREACHABLE)
 }
 
 pub fn solve_part1(grid: &PreparedInput) -> u32 {
-    solve_part(grid, 1..=3)
+    try_solve_part1(grid).unwrap()
+}
+
+pub fn try_solve_part2(grid: &PreparedInput) -> Result<u32, Error> {
+    solve_part(grid, 4..=10).ok_or(UNREACHABLE)
 }
 
 pub fn solve_part2(grid: &PreparedInput) -> u32 {
-    solve_part(grid, 4..=10)
+    try_solve_part2(grid).unwrap()
+}
+
+pub fn try_solve(input: &str) -> Result<(Solution, Solution), Error> {
+    let input = try_prepare(input)?;
+    Ok((
+        try_solve_part1(&input)?.into(),
+        try_solve_part2(&input)?.into(),
+    ))
 }
 
 pub fn solve(input: &str) -> (Solution, Solution) {
-    let input = prepare(input);
-    (solve_part1(&input).into(), solve_part2(&input).into())
+    try_solve(input).unwrap()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::generate::{self, assert_matches_reference};
+    use crate::util::position::Dimensions;
 
     const EXAMPLE_INPUT: &str = "2413432311323
 3215453535623
@@ -151,13 +210,30 @@ mod tests {
 4322674655533";
     #[test]
     fn example_prepare() {
-        assert_eq!(prepare(EXAMPLE_INPUT).dimensions, (13, 13));
+        assert_eq!(
+            prepare(EXAMPLE_INPUT).dimensions,
+            Dimensions::from_height_width(13, 13)
+        );
     }
     #[test]
     fn example_part1() {
         assert_eq!(solve_part1(&prepare(EXAMPLE_INPUT)), 102);
     }
     #[test]
+    fn example_render() {
+        let text = render(EXAMPLE_INPUT).unwrap().with_color(false).to_string();
+        assert_eq!(
+            text.lines().take(5).collect::<Vec<_>>(),
+            [
+                "2>>34^>>>1323",
+                "32v>>>35v5623",
+                "32552456v>>54",
+                "3446585845v52",
+                "4546657867v>6"
+            ]
+        );
+    }
+    #[test]
     fn example_part2a() {
         assert_eq!(solve_part2(&prepare(EXAMPLE_INPUT)), 94);
     }
@@ -174,4 +250,56 @@ mod tests {
             71
         );
     }
+
+    /// Plain Dijkstra over single tile moves, tracking the direction and length of the current run.
+    fn reference(input: &str, min_run: usize, max_run: usize) -> u32 {
+        use std::cmp::Reverse;
+        use std::collections::{BinaryHeap, HashSet};
+
+        let tiles = input.lines().map(str::as_bytes).collect::<Vec<_>>();
+        let (height, width) = (tiles.len() as isize, tiles[0].len() as isize);
+        let directions = [(-1, 0), (0, 1), (1, 0), (0, -1)];
+
+        let mut visited = HashSet::new();
+        // A run of length 0 only happens at the start, where any direction can be taken.
+        let mut queue = BinaryHeap::from([Reverse((0u32, 0isize, 0isize, 0usize, 0usize))]);
+        while let Some(Reverse((heat_loss, y, x, direction, run))) = queue.pop() {
+            if (y, x) == (height - 1, width - 1) && run >= min_run {
+                return heat_loss;
+            }
+            if !visited.insert((y, x, direction, run)) {
+                continue;
+            }
+            for (next_direction, (dy, dx)) in directions.iter().enumerate() {
+                let straight = next_direction == direction;
+                if run > 0 && next_direction == (direction + 2) % 4
+                    || run > 0 && !straight && run < min_run
+                    || straight && run == max_run
+                {
+                    continue;
+                }
+                let (y, x) = (y + dy, x + dx);
+                if !(0..height).contains(&y) || !(0..width).contains(&x) {
+                    continue;
+                }
+                let run = if straight && run > 0 { run + 1 } else { 1 };
+                let heat_loss = heat_loss + (tiles[y as usize][x as usize] - b'0') as u32;
+                queue.push(Reverse((heat_loss, y, x, next_direction, run)));
+            }
+        }
+        panic!("The target can not be reached")
+    }
+    #[test]
+    fn matches_reference() {
+        assert_matches_reference(
+            generate::day17::generate,
+            10,
+            &[0.02],
+            |input| {
+                let prepared = prepare(input);
+                (solve_part1(&prepared), solve_part2(&prepared))
+            },
+            |input| (reference(input, 1, 3), reference(input, 4, 10)),
+        );
+    }
 }
@@ -1,3 +1,7 @@
+use crate::solution::Solution;
+use std::fmt::{Display, Formatter};
+use std::hint::black_box;
+
 pub mod day01;
 pub mod day02;
 pub mod day03;
@@ -15,3 +19,119 @@ pub mod day14;
 pub mod day15;
 pub mod day16;
 pub mod day17;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum Phase {
+    Prepare,
+    Part1,
+    Part2,
+    /// Used by days that parse lazily while solving and therefore cannot be split up.
+    Solve,
+}
+
+impl Phase {
+    pub const ALL: [Phase; 4] = [Phase::Prepare, Phase::Part1, Phase::Part2, Phase::Solve];
+}
+
+impl Display for Phase {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Phase::Prepare => "prepare",
+            Phase::Part1 => "part1",
+            Phase::Part2 => "part2",
+            Phase::Solve => "solve",
+        })
+    }
+}
+
+/// Wraps the execution of every phase of a day, e.g. to time it.
+///
+/// `run` may be called any number of times, the result of the last call is used by the next phase.
+pub trait Measure {
+    fn measure(&mut self, phase: Phase, run: &mut dyn FnMut());
+}
+
+pub struct Day {
+    pub number: usize,
+    pub solve: fn(&str) -> (Solution, Solution),
+    pub phases: fn(&str, &mut dyn Measure) -> (Solution, Solution),
+}
+
+macro_rules! phases {
+    ($day:ident) => {
+        phases!($day::prepare, $day::solve_part1, $day::solve_part2)
+    };
+    ($day:ident, solve) => {
+        |input: &str, measure: &mut dyn Measure| -> (Solution, Solution) {
+            let mut solution = None;
+            measure.measure(Phase::Solve, &mut || {
+                solution = Some($day::solve(black_box(input)));
+            });
+            solution.unwrap()
+        }
+    };
+    ($prepare:expr, $part1:expr, $part2:expr) => {
+        |input: &str, measure: &mut dyn Measure| -> (Solution, Solution) {
+            let mut prepared = None;
+            measure.measure(Phase::Prepare, &mut || {
+                prepared = Some($prepare(black_box(input)));
+            });
+            let prepared = prepared.unwrap();
+
+            let mut part1 = None;
+            measure.measure(Phase::Part1, &mut || {
+                part1 = Some($part1(black_box(&prepared)));
+            });
+            let mut part2 = None;
+            measure.measure(Phase::Part2, &mut || {
+                part2 = Some($part2(black_box(&prepared)));
+            });
+            (part1.unwrap().into(), part2.unwrap().into())
+        }
+    };
+}
+
+macro_rules! day {
+    ($number:literal, $day:ident) => {
+        Day {
+            number: $number,
+            solve: $day::solve,
+            phases: phases!($day),
+        }
+    };
+    ($number:literal, $day:ident, solve) => {
+        Day {
+            number: $number,
+            solve: $day::solve,
+            phases: phases!($day, solve),
+        }
+    };
+}
+
+pub const DAYS: [Day; 17] = [
+    day!(1, day01),
+    day!(2, day02, solve),
+    day!(3, day03),
+    day!(4, day04, solve),
+    day!(5, day05),
+    Day {
+        number: 6,
+        solve: day06::solve,
+        phases: phases!(
+            |input| (day06::prepare_part1(input), day06::prepare_part2(input)),
+            |input: &(Vec<day06::Race>, day06::Race)| day06::solve_part(&input.0),
+            |input: &(Vec<day06::Race>, day06::Race)| day06::solve_part(&[input.1])
+        ),
+    },
+    day!(7, day07),
+    day!(8, day08),
+    day!(9, day09, solve),
+    day!(10, day10),
+    day!(11, day11),
+    day!(12, day12),
+    day!(13, day13),
+    day!(14, day14),
+    day!(15, day15),
+    day!(16, day16),
+    day!(17, day17),
+];
@@ -0,0 +1,62 @@
+use std::fmt::{Display, Formatter};
+
+/// Reasons an input can not be solved.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Error {
+    /// The input does not follow the puzzle format, at a 1-based line and column.
+    Parse { line: usize, column: usize },
+    /// The input is well-formed but breaks an assumption the solution relies on.
+    Invalid(&'static str),
+}
+
+impl Error {
+    /// Parse error at a byte offset into the input.
+    pub(crate) fn parse_at(input: &[u8], offset: usize) -> Self {
+        let before = &input[..offset.min(input.len())];
+        let line_start = before
+            .iter()
+            .rposition(|&c| c == b'\n')
+            .map_or(0, |i| i + 1);
+        Error::Parse {
+            line: before.iter().filter(|&&c| c == b'\n').count() + 1,
+            column: before.len() - line_start + 1,
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Parse { line, column } => write!(f, "unexpected input at {}:{}", line, column),
+            Error::Invalid(reason) => write!(f, "invalid input: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_at() {
+        let input = b"12\n345\n6";
+        assert_eq!(
+            Error::parse_at(input, 0),
+            Error::Parse { line: 1, column: 1 }
+        );
+        assert_eq!(
+            Error::parse_at(input, 2),
+            Error::Parse { line: 1, column: 3 }
+        );
+        assert_eq!(
+            Error::parse_at(input, 4),
+            Error::Parse { line: 2, column: 2 }
+        );
+        assert_eq!(
+            Error::parse_at(input, 8),
+            Error::Parse { line: 3, column: 2 }
+        );
+    }
+}
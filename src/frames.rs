@@ -0,0 +1,193 @@
+//! Snapshots of grids taken while a solution runs, to watch a simulation afterwards.
+//!
+//! Solutions call `record_frame!` at interesting points. With the `record-frames` feature
+//! disabled the macro expands to nothing, otherwise it takes a snapshot whenever a recording was
+//! started with [`start`] and the throttling in [`Settings`] lets it through.
+
+use crate::util::grid::{Grid, GridView};
+use crate::util::image::{write_ppm, CellColor};
+use crate::util::position::Dimensions;
+use std::cell::RefCell;
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::time::Duration;
+
+#[derive(Copy, Clone, Debug)]
+pub struct Settings {
+    /// Only every `every`-th call of the hook takes a snapshot.
+    pub every: usize,
+    /// Snapshots after this many are dropped.
+    pub max_frames: usize,
+}
+
+pub struct Frame {
+    label: &'static str,
+    image: Grid<[u8; 3]>,
+}
+
+impl Frame {
+    pub fn label(&self) -> &'static str {
+        self.label
+    }
+
+    pub fn dimensions(&self) -> Dimensions {
+        self.image.dimensions
+    }
+}
+
+struct Recording {
+    settings: Settings,
+    calls: usize,
+    frames: Vec<Frame>,
+}
+
+thread_local! {
+    static RECORDING: RefCell<Option<Recording>> = const { RefCell::new(None) };
+}
+
+/// Starts recording the snapshots taken on this thread.
+pub fn start(settings: Settings) {
+    assert!(settings.every > 0);
+    RECORDING.set(Some(Recording {
+        settings,
+        calls: 0,
+        frames: vec![],
+    }));
+}
+
+/// Stops recording and returns the snapshots taken since [`start`].
+pub fn finish() -> Vec<Frame> {
+    RECORDING
+        .take()
+        .map_or_else(Vec::new, |recording| recording.frames)
+}
+
+/// Counts a call of a hook and returns whether it should take a snapshot.
+#[doc(hidden)]
+pub fn wants_frame() -> bool {
+    RECORDING.with_borrow_mut(|recording| {
+        let Some(recording) = recording else {
+            return false;
+        };
+        recording.calls += 1;
+        (recording.calls - 1) % recording.settings.every == 0
+            && recording.frames.len() < recording.settings.max_frames
+    })
+}
+
+#[doc(hidden)]
+pub fn record<G>(label: &'static str, grid: &G)
+where
+    G: GridView,
+    G::Cell: CellColor,
+{
+    let image = Grid::from_rows(grid.rows().map(|row| row.map(CellColor::color)));
+    RECORDING.with_borrow_mut(|recording| {
+        if let Some(recording) = recording {
+            recording.frames.push(Frame { label, image });
+        }
+    });
+}
+
+/// Takes a snapshot of a grid whose cells implement `CellColor`. The grid expression is only
+/// evaluated when a snapshot is taken.
+macro_rules! record_frame {
+    ($label:expr, $grid:expr) => {
+        #[cfg(feature = "record-frames")]
+        {
+            if $crate::frames::wants_frame() {
+                $crate::frames::record($label, $grid);
+            }
+        }
+    };
+}
+pub(crate) use record_frame;
+
+/// Writes every frame to `<label>_<index>.ppm` in `directory`, with `scale` pixels per cell.
+pub fn write_ppm_sequence(frames: &[Frame], directory: &Path, scale: usize) -> io::Result<()> {
+    std::fs::create_dir_all(directory)?;
+    for (i, frame) in frames.iter().enumerate() {
+        let path = directory.join(format!("{}_{:05}.ppm", frame.label, i));
+        let mut out = BufWriter::new(File::create(path)?);
+        write_ppm(&frame.image, scale, &mut out)?;
+        out.flush()?;
+    }
+    Ok(())
+}
+
+/// Plays the frames back in a terminal with true colour support, two rows per line of text.
+pub fn replay(frames: &[Frame], out: &mut impl Write, delay: Duration) -> io::Result<()> {
+    let mut text = String::new();
+    for (i, frame) in frames.iter().enumerate() {
+        text.clear();
+        // Home the cursor and clear the screen.
+        text.push_str("\x1b[H\x1b[2J");
+        let (height, width) = (frame.dimensions().height(), frame.dimensions().width());
+        for y in (0..height).step_by(2) {
+            for x in 0..width {
+                let [r, g, b] = frame.image[y][x];
+                let [br, bg, bb] = match y + 1 < height {
+                    true => frame.image[y + 1][x],
+                    false => [0; 3],
+                };
+                // The upper half block shows the top cell as foreground and the one below as background.
+                let _ = write!(
+                    text,
+                    "\x1b[38;2;{};{};{}m\x1b[48;2;{};{};{}m▀",
+                    r, g, b, br, bg, bb
+                );
+            }
+            text.push_str("\x1b[0m\n");
+        }
+        let _ = writeln!(text, "{} {}/{}", frame.label, i + 1, frames.len());
+        out.write_all(text.as_bytes())?;
+        out.flush()?;
+        std::thread::sleep(delay);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn throttling() {
+        assert!(!wants_frame());
+        start(Settings {
+            every: 3,
+            max_frames: 2,
+        });
+        let grid = Grid::from_rows([[true, false]]);
+        let taken = (0..10)
+            .filter(|_| {
+                let wanted = wants_frame();
+                if wanted {
+                    record("test", &grid);
+                }
+                wanted
+            })
+            .count();
+        assert_eq!(taken, 2);
+        let frames = finish();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].dimensions(), Dimensions::from_height_width(1, 2));
+        assert!(!wants_frame());
+        assert!(finish().is_empty());
+    }
+    #[test]
+    fn replay_text() {
+        start(Settings {
+            every: 1,
+            max_frames: 1,
+        });
+        record("test", &Grid::from_rows([[true], [false], [true]]));
+        let mut out = vec![];
+        replay(&finish(), &mut out, Duration::ZERO).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert_eq!(text.matches('▀').count(), 2);
+        assert!(text.contains("\x1b[38;2;255;255;255m\x1b[48;2;0;0;0m▀"));
+    }
+}
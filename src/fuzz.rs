@@ -0,0 +1,163 @@
+//! Entry points for fuzzing every day, driven by the targets in the `fuzz` directory.
+//!
+//! Each entry point solves both parts for arbitrary bytes. Inputs the solutions can not handle are
+//! rejected with an [`Error`], which the entry point ignores. Anything else that goes wrong (a
+//! panic, an overflow or a hang) is a bug.
+//!
+//! Run a target with `cargo fuzz run day05`, after installing `cargo-fuzz`.
+
+use crate::day::*;
+use crate::error::Error;
+use crate::solution::Solution;
+
+fn to_str(data: &[u8]) -> Result<&str, Error> {
+    std::str::from_utf8(data).map_err(|err| Error::parse_at(data, err.valid_up_to()))
+}
+
+/// Solves both parts, with the input checked to be UTF-8 first.
+fn try_solve(
+    try_solve_day: fn(&str) -> Result<(Solution, Solution), Error>,
+    data: &[u8],
+) -> Result<(), Error> {
+    try_solve_day(to_str(data)?).map(|_| ())
+}
+
+macro_rules! fuzz_day {
+    ($name:ident, $day:ident) => {
+        pub fn $name(data: &[u8]) {
+            // Rejecting the input with an error is fine, only panics count as failures.
+            let _ = try_solve($day::try_solve, data);
+        }
+    };
+}
+
+fuzz_day!(fuzz_day01, day01);
+fuzz_day!(fuzz_day02, day02);
+fuzz_day!(fuzz_day03, day03);
+fuzz_day!(fuzz_day04, day04);
+fuzz_day!(fuzz_day05, day05);
+fuzz_day!(fuzz_day06, day06);
+fuzz_day!(fuzz_day07, day07);
+fuzz_day!(fuzz_day08, day08);
+fuzz_day!(fuzz_day09, day09);
+fuzz_day!(fuzz_day10, day10);
+fuzz_day!(fuzz_day11, day11);
+fuzz_day!(fuzz_day12, day12);
+fuzz_day!(fuzz_day13, day13);
+fuzz_day!(fuzz_day14, day14);
+fuzz_day!(fuzz_day15, day15);
+fuzz_day!(fuzz_day16, day16);
+fuzz_day!(fuzz_day17, day17);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate::generate;
+    use crate::util::rng::Rng;
+
+    type TrySolve = fn(&str) -> Result<(Solution, Solution), Error>;
+
+    const TRY_SOLVE: [TrySolve; 17] = [
+        day01::try_solve,
+        day02::try_solve,
+        day03::try_solve,
+        day04::try_solve,
+        day05::try_solve,
+        day06::try_solve,
+        day07::try_solve,
+        day08::try_solve,
+        day09::try_solve,
+        day10::try_solve,
+        day11::try_solve,
+        day12::try_solve,
+        day13::try_solve,
+        day14::try_solve,
+        day15::try_solve,
+        day16::try_solve,
+        day17::try_solve,
+    ];
+    const ENTRY_POINTS: [fn(&[u8]); 17] = [
+        fuzz_day01, fuzz_day02, fuzz_day03, fuzz_day04, fuzz_day05, fuzz_day06, fuzz_day07,
+        fuzz_day08, fuzz_day09, fuzz_day10, fuzz_day11, fuzz_day12, fuzz_day13, fuzz_day14,
+        fuzz_day15, fuzz_day16, fuzz_day17,
+    ];
+
+    /// Applies a few random byte edits, mostly using bytes which already occur in the input.
+    fn mutate(rng: &mut Rng, data: &mut Vec<u8>) {
+        const SPECIAL: &[u8] = b"\n 0019:,=-#.?SAZ\xff";
+        for _ in 0..rng.range(1..4) {
+            if data.is_empty() {
+                data.push(*rng.choose(SPECIAL));
+                continue;
+            }
+            let i = rng.index(data.len());
+            let byte = if rng.chance(0.5) {
+                data[rng.index(data.len())]
+            } else {
+                *rng.choose(SPECIAL)
+            };
+            match rng.index(4) {
+                0 => data[i] = byte,
+                1 => data.insert(i, byte),
+                2 => {
+                    data.remove(i);
+                }
+                _ => data.truncate(i),
+            }
+        }
+    }
+
+    #[test]
+    fn generated_inputs_are_accepted() {
+        for (day, try_solve_day) in TRY_SOLVE.into_iter().enumerate() {
+            let input = generate(day + 1, 0, 0.02);
+            assert_eq!(
+                try_solve(try_solve_day, input.as_bytes()),
+                Ok(()),
+                "day{}",
+                day + 1
+            );
+        }
+    }
+    #[test]
+    fn mutated_inputs_do_not_panic() {
+        let mut rng = Rng::from_seed(0);
+        for (day, entry_point) in ENTRY_POINTS.iter().enumerate() {
+            for seed in 0..200 {
+                let mut data = generate(day + 1, seed, 0.01).into_bytes();
+                mutate(&mut rng, &mut data);
+                entry_point(&data);
+            }
+        }
+    }
+    #[test]
+    fn rejected_inputs() {
+        let inputs: [(usize, &[u8]); 12] = [
+            (2, b"Game 1: 256 red"),
+            (3, b"\r\n"),
+            (5, b"seeds: 1\n\nx map:\n1 2 3"),
+            (6, b"Time: 18446744073709551615\nDistance: 8"),
+            (7, b"AAAA"),
+            (8, b"L\n\nAAA = (AAA, AAA)"),
+            (8, b"L\n\nAAAA = (AAA, AAA)"),
+            (10, b"S-7\n|.|\nL-."),
+            (
+                12,
+                b"?????????????????????????????????????????????????????? 1,1,1,1",
+            ),
+            (13, b"#.\n.#"),
+            (15, b"rn=1,"),
+            (17, b"12\n34"),
+        ];
+        for (day, input) in inputs {
+            assert!(try_solve(TRY_SOLVE[day - 1], input).is_err(), "day{}", day);
+        }
+    }
+    #[test]
+    fn invalid_utf8() {
+        assert_eq!(
+            try_solve(day01::try_solve, b"12\n3\xff"),
+            Err(Error::Parse { line: 2, column: 2 })
+        );
+    }
+}
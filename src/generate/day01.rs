@@ -0,0 +1,31 @@
+use crate::generate::scaled;
+use crate::util::rng::Rng;
+
+const WORDS: [&str; 9] = [
+    "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+];
+
+fn generate_line(rng: &mut Rng) -> String {
+    let length = rng.range(1..40) as usize;
+    let mut line = String::new();
+    while line.len() < length {
+        match rng.index(4) {
+            0 => line.push(char::from(b'1' + rng.index(9) as u8)),
+            1 => line.push_str(WORDS[rng.index(WORDS.len())]),
+            _ => line.push(char::from(b'a' + rng.index(26) as u8)),
+        }
+    }
+    // Part 1 needs at least one digit on every line.
+    if !line.bytes().any(|c| c.is_ascii_digit()) {
+        let i = rng.index(line.len() + 1);
+        line.insert(i, char::from(b'1' + rng.index(9) as u8));
+    }
+    line
+}
+
+pub fn generate(rng: &mut Rng, scale: f64) -> String {
+    (0..scaled(1000, scale))
+        .map(|_| generate_line(rng))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
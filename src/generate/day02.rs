@@ -0,0 +1,24 @@
+use crate::generate::scaled;
+use crate::util::rng::Rng;
+
+pub fn generate(rng: &mut Rng, scale: f64) -> String {
+    (0..scaled(100, scale))
+        .map(|i| {
+            let draws = (0..rng.range(1..7))
+                .map(|_| {
+                    let mut colors = ["red", "green", "blue"];
+                    rng.shuffle(&mut colors);
+                    colors[..rng.range(1..4) as usize]
+                        .iter()
+                        .map(|color| format!("{} {}", rng.range(1..21), color))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                })
+                .collect::<Vec<_>>()
+                .join("; ");
+            // Game ids are stored as a u8, so they wrap around for large inputs.
+            format!("Game {}: {}", i % u8::MAX as usize + 1, draws)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,40 @@
+use crate::generate::{render_grid, scaled_side};
+use crate::util::rng::Rng;
+
+const SYMBOLS: &[u8] = b"*#+$/@=%&-";
+
+pub fn generate(rng: &mut Rng, scale: f64) -> String {
+    let side = scaled_side(140, scale).max(3);
+    let mut rows = vec![vec![b'.'; side]; side];
+
+    for row in rows.iter_mut() {
+        let mut i = 0;
+        while i < side {
+            if rng.chance(0.1) {
+                let length = (rng.range(1..4) as usize).min(side - i);
+                row[i] = b'1' + rng.index(9) as u8;
+                for cell in &mut row[i + 1..i + length] {
+                    *cell = b'0' + rng.index(10) as u8;
+                }
+                // Keep at least one empty cell between numbers.
+                i += length + 1;
+            } else {
+                i += 1;
+            }
+        }
+    }
+
+    for row in rows.iter_mut() {
+        for cell in row.iter_mut() {
+            if *cell == b'.' && rng.chance(0.05) {
+                *cell = if rng.chance(0.4) {
+                    b'*'
+                } else {
+                    *rng.choose(SYMBOLS)
+                };
+            }
+        }
+    }
+
+    render_grid(&rows)
+}
@@ -0,0 +1,50 @@
+use crate::generate::scaled;
+use crate::util::rng::Rng;
+
+fn distinct_numbers(rng: &mut Rng, count: usize, excluded: &[u8]) -> Vec<u8> {
+    let mut numbers = (1..100u8)
+        .filter(|num| !excluded.contains(num))
+        .collect::<Vec<_>>();
+    rng.shuffle(&mut numbers);
+    numbers.truncate(count);
+    numbers
+}
+
+fn format_numbers(numbers: &[u8]) -> String {
+    numbers
+        .iter()
+        .map(|num| format!("{:>2}", num))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+pub fn generate(rng: &mut Rng, scale: f64) -> String {
+    let card_count = scaled(200, scale);
+    // Cards directly following a card with matches get none of their own. This keeps the number of
+    // copies from growing exponentially and overflowing.
+    let mut cooldown = 0;
+    (0..card_count)
+        .map(|i| {
+            let matches = if cooldown > 0 {
+                cooldown -= 1;
+                0
+            } else {
+                rng.range(0..11).min((card_count - i - 1) as u64) as usize
+            };
+            cooldown = cooldown.max(matches);
+
+            let winning = distinct_numbers(rng, 10, &[]);
+            let mut numbers = distinct_numbers(rng, 25 - matches, &winning);
+            numbers.extend(&winning[..matches]);
+            rng.shuffle(&mut numbers);
+
+            format!(
+                "Card {:>3}: {} | {}",
+                i + 1,
+                format_numbers(&winning),
+                format_numbers(&numbers)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,68 @@
+use crate::generate::scaled;
+use crate::util::rng::Rng;
+
+const MAPS: [&str; 7] = [
+    "seed-to-soil",
+    "soil-to-fertilizer",
+    "fertilizer-to-water",
+    "water-to-light",
+    "light-to-temperature",
+    "temperature-to-humidity",
+    "humidity-to-location",
+];
+
+/// Splits `0..universe` into consecutive ranges of which a random selection is mapped.
+fn generate_map(rng: &mut Rng, universe: u64, range_count: usize) -> String {
+    let mut cuts = (0..range_count)
+        .map(|_| rng.range(0..universe))
+        .collect::<Vec<_>>();
+    cuts.push(0);
+    cuts.push(universe);
+    cuts.sort_unstable();
+    cuts.dedup();
+
+    let mut lines = cuts
+        .windows(2)
+        .filter_map(|window| {
+            if !rng.chance(0.8) {
+                return None;
+            }
+            let length = window[1] - window[0];
+            let output_start = rng.range(0..universe - length + 1);
+            Some(format!("{} {} {}", output_start, window[0], length))
+        })
+        .collect::<Vec<_>>();
+    if lines.is_empty() {
+        lines.push(format!("{} {} {}", rng.range(0..universe), 0, 1));
+    }
+    rng.shuffle(&mut lines);
+    lines.join("\n")
+}
+
+pub fn generate(rng: &mut Rng, scale: f64) -> String {
+    // Smaller inputs use much smaller numbers, so they can be checked by brute force.
+    let universe = ((u32::MAX as f64 * scale.min(1.0).powi(4)) as u64).max(100);
+
+    let seeds = (0..scaled(10, scale))
+        .map(|_| {
+            let start = rng.range(0..universe);
+            let length = rng.range(1..(universe - start).min(universe / 10).max(1) + 1);
+            format!("{} {}", start, length)
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let maps = MAPS
+        .iter()
+        .map(|name| {
+            format!(
+                "{} map:\n{}",
+                name,
+                generate_map(rng, universe, scaled(30, scale))
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n");
+
+    format!("seeds: {}\n\n{}", seeds, maps)
+}
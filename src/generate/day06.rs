@@ -0,0 +1,42 @@
+use crate::util::rng::Rng;
+
+fn concatenated(numbers: &[u64]) -> u128 {
+    numbers
+        .iter()
+        .map(|num| num.to_string())
+        .collect::<String>()
+        .parse()
+        .unwrap()
+}
+
+pub fn generate(rng: &mut Rng, scale: f64) -> String {
+    // Part 2 concatenates all numbers, so the input can not grow beyond four two digit races.
+    let race_count = ((4.0 * scale).round() as usize).clamp(1, 4);
+    let max_time = ((100.0 * scale) as u64).clamp(8, 100);
+
+    loop {
+        let times = (0..race_count)
+            .map(|_| rng.range(7..max_time))
+            .collect::<Vec<_>>();
+        // Every race can be won, the record is below the best possible distance.
+        let distances = times
+            .iter()
+            .map(|time| rng.range(0..time * time / 4))
+            .collect::<Vec<_>>();
+
+        let (time, distance) = (concatenated(&times), concatenated(&distances));
+        if distance < time * time / 4 {
+            return format!(
+                "Time:{}\nDistance:{}",
+                times
+                    .iter()
+                    .map(|time| format!("{:>7}", time))
+                    .collect::<String>(),
+                distances
+                    .iter()
+                    .map(|distance| format!("{:>7}", distance))
+                    .collect::<String>(),
+            );
+        }
+    }
+}
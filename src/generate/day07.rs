@@ -0,0 +1,23 @@
+use crate::generate::scaled;
+use crate::util::rng::Rng;
+use rustc_hash::FxHashSet;
+
+const CARDS: &[u8] = b"23456789TJQKA";
+
+pub fn generate(rng: &mut Rng, scale: f64) -> String {
+    // Equal hands would make the ranking ambiguous.
+    let hand_count = scaled(1000, scale).min(CARDS.len().pow(5));
+    let mut hands = FxHashSet::default();
+    let mut lines = vec![];
+    while lines.len() < hand_count {
+        let hand = (0..5).map(|_| *rng.choose(CARDS)).collect::<Vec<_>>();
+        if hands.insert(hand.clone()) {
+            lines.push(format!(
+                "{} {}",
+                String::from_utf8(hand).unwrap(),
+                rng.range(1..1001)
+            ));
+        }
+    }
+    lines.join("\n")
+}
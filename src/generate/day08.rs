@@ -0,0 +1,109 @@
+use crate::util::rng::Rng;
+use rustc_hash::FxHashSet;
+
+const PRIMES: [usize; 15] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47];
+const NAME_CHARS: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+struct Names(FxHashSet<String>);
+
+impl Names {
+    /// A new unique name which ends with `last`, or with anything but `A` and `Z` if not given.
+    fn next(&mut self, rng: &mut Rng, last: Option<u8>) -> String {
+        loop {
+            let last = last.unwrap_or_else(|| loop {
+                let c = *rng.choose(NAME_CHARS);
+                if c != b'A' && c != b'Z' {
+                    break c;
+                }
+            });
+            let name =
+                String::from_utf8(vec![*rng.choose(NAME_CHARS), *rng.choose(NAME_CHARS), last])
+                    .unwrap();
+            if self.0.insert(name.clone()) {
+                return name;
+            }
+        }
+    }
+}
+
+fn children(rng: &mut Rng, next: &[String]) -> String {
+    let mut next = next.to_vec();
+    if next.len() == 1 {
+        next.push(next[0].clone());
+    }
+    rng.shuffle(&mut next);
+    format!("({}, {})", next[0], next[1])
+}
+
+/// Adds a loop of `length` steps that starts at `start` and returns to the step after it, with `end`
+/// reached exactly once per loop. Along the way the directions choose between two equivalent nodes.
+fn generate_loop(
+    rng: &mut Rng,
+    names: &mut Names,
+    start: String,
+    end: String,
+    length: usize,
+    lines: &mut Vec<String>,
+) {
+    let mut steps = (1..length)
+        .map(|_| {
+            let count = if rng.chance(0.3) { 2 } else { 1 };
+            (0..count)
+                .map(|_| names.next(rng, None))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    steps.push(vec![end.clone()]);
+
+    // The start and end have to lead to the same node for any direction, otherwise the walk after
+    // the first loop would differ from the first loop.
+    let first = children(rng, &steps[0]);
+    lines.push(format!("{} = {}", start, first));
+    lines.push(format!("{} = {}", end, first));
+    for (step, next) in steps.iter().zip(steps.iter().skip(1)) {
+        for name in step {
+            lines.push(format!("{} = {}", name, children(rng, next)));
+        }
+    }
+}
+
+pub fn generate(rng: &mut Rng, scale: f64) -> String {
+    // The names are limited to three characters, so larger scales keep the size of the real input.
+    let max_prime = (50.0 * scale.sqrt()).clamp(3.0, 47.0) as usize;
+    let mut primes = PRIMES
+        .into_iter()
+        .filter(|prime| *prime <= max_prime)
+        .collect::<Vec<_>>();
+    rng.shuffle(&mut primes);
+
+    // Every loop is a multiple of the number of directions. This is what the solver relies upon.
+    let direction_count = primes.pop().unwrap();
+    let ghost_count = ((6.0 * scale).round() as usize).clamp(1, primes.len().clamp(1, 6));
+    let directions = (0..direction_count)
+        .map(|_| if rng.chance(0.5) { 'L' } else { 'R' })
+        .collect::<String>();
+
+    let mut names = Names(FxHashSet::default());
+    names.0.insert("AAA".to_string());
+    names.0.insert("ZZZ".to_string());
+
+    let mut lines = vec![];
+    for (ghost, prime) in primes.iter().take(ghost_count).enumerate() {
+        let (start, end) = if ghost == 0 {
+            ("AAA".to_string(), "ZZZ".to_string())
+        } else {
+            (names.next(rng, Some(b'A')), names.next(rng, Some(b'Z')))
+        };
+        generate_loop(
+            rng,
+            &mut names,
+            start,
+            end,
+            direction_count * prime,
+            &mut lines,
+        );
+    }
+    rng.shuffle(&mut lines);
+
+    format!("{}\n\n{}", directions, lines.join("\n"))
+}
@@ -0,0 +1,32 @@
+use crate::generate::scaled;
+use crate::util::rng::Rng;
+
+/// Binomial coefficient, also for negative `n`.
+fn binomial(n: i64, k: i64) -> i64 {
+    (0..k).fold(1, |acc, i| acc * (n - i) / (i + 1))
+}
+
+pub fn generate(rng: &mut Rng, scale: f64) -> String {
+    let length = 21;
+    (0..scaled(200, scale))
+        .map(|_| {
+            // In the binomial basis every coefficient is the first value of one difference sequence.
+            // The degree stays below the length so the differences always reach zero.
+            let coefficients = (0..rng.range(1..11))
+                .map(|_| rng.range(0..41) as i64 - 20)
+                .collect::<Vec<_>>();
+            (0..length)
+                .map(|x| {
+                    coefficients
+                        .iter()
+                        .enumerate()
+                        .map(|(k, c)| c * binomial(x, k as i64))
+                        .sum::<i64>()
+                        .to_string()
+                })
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
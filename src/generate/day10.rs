@@ -0,0 +1,154 @@
+use crate::generate::{render_grid, scaled_side};
+use crate::util::rng::Rng;
+
+type Cell = (usize, usize);
+
+fn offset(cell: Cell, (dy, dx): (isize, isize), side: usize) -> Option<Cell> {
+    let y = cell.0.checked_add_signed(dy).filter(|y| *y < side)?;
+    let x = cell.1.checked_add_signed(dx).filter(|x| *x < side)?;
+    Some((y, x))
+}
+
+fn pipe(a: (isize, isize), b: (isize, isize)) -> u8 {
+    match (a.min(b), a.max(b)) {
+        ((-1, 0), (1, 0)) => b'|',
+        ((0, -1), (0, 1)) => b'-',
+        ((-1, 0), (0, 1)) => b'L',
+        ((-1, 0), (0, -1)) => b'J',
+        ((0, -1), (1, 0)) => b'7',
+        ((0, 1), (1, 0)) => b'F',
+        _ => unreachable!(),
+    }
+}
+
+fn delta(from: Cell, to: Cell) -> (isize, isize) {
+    (
+        to.0 as isize - from.0 as isize,
+        to.1 as isize - from.1 as isize,
+    )
+}
+
+type Links = Vec<Vec<Option<Cell>>>;
+
+fn link(next: &mut Links, previous: &mut Links, path: &[Cell]) {
+    for pair in path.windows(2) {
+        next[pair[0].0][pair[0].1] = Some(pair[1]);
+        previous[pair[1].0][pair[1].1] = Some(pair[0]);
+    }
+}
+
+/// Grows a simple loop from a 2x2 square through random local changes which only ever add free
+/// cells to the loop, so it never crosses itself:
+/// - An edge `b`, `c` takes a detour `b`, `b'`, `c'`, `c` through two free neighbouring cells.
+/// - The middle of a straight `a`, `b`, `c` is pushed aside to `a`, `a'`, `b'`, `c'`, `c`.
+/// - A corner `a`, `b`, `c` is flipped to the opposite diagonal `a`, `d`, `c`.
+///
+/// Returns the successor of every cell that is part of the loop.
+fn generate_loop(rng: &mut Rng, side: usize) -> Links {
+    let mut next = vec![vec![None; side]; side];
+    let mut previous = vec![vec![None; side]; side];
+    let (y, x) = (rng.index(side - 1), rng.index(side - 1));
+    let mut cells = vec![(y, x), (y, x + 1), (y + 1, x + 1), (y + 1, x), (y, x)];
+    link(&mut next, &mut previous, &cells);
+    cells.pop();
+
+    let mut length = cells.len();
+    let target_length = side * side / 2;
+    for _ in 0..20 * side * side {
+        if length >= target_length {
+            break;
+        }
+        let i = rng.index(cells.len());
+        let b = cells[i];
+        let Some(c) = next[b.0][b.1] else {
+            // Removed from the loop by an earlier change.
+            cells.swap_remove(i);
+            continue;
+        };
+        let a = previous[b.0][b.1].unwrap();
+
+        let (dy, dx) = delta(b, c);
+        let aside = if rng.chance(0.5) {
+            (dx, -dy)
+        } else {
+            (-dx, dy)
+        };
+        let path = if rng.chance(0.5) {
+            let (Some(b2), Some(c2)) = (offset(b, aside, side), offset(c, aside, side)) else {
+                continue;
+            };
+            vec![b, b2, c2, c]
+        } else if delta(a, b) == (dy, dx) {
+            let (Some(a2), Some(b2), Some(c2)) = (
+                offset(a, aside, side),
+                offset(b, aside, side),
+                offset(c, aside, side),
+            ) else {
+                continue;
+            };
+            vec![a, a2, b2, c2, c]
+        } else {
+            let (ay, ax) = delta(b, a);
+            let Some(d) = offset(b, (ay + dy, ax + dx), side) else {
+                continue;
+            };
+            vec![a, d, c]
+        };
+
+        let added = &path[1..path.len() - 1];
+        if added.iter().any(|cell| next[cell.0][cell.1].is_some()) {
+            continue;
+        }
+        let is_detour = path[0] == b;
+        if !is_detour {
+            next[b.0][b.1] = None;
+            previous[b.0][b.1] = None;
+            length -= 1;
+        }
+        link(&mut next, &mut previous, &path);
+        cells.extend(added);
+        length += added.len();
+    }
+    next
+}
+
+pub fn generate(rng: &mut Rng, scale: f64) -> String {
+    let side = scaled_side(140, scale).max(3);
+    let next = generate_loop(rng, side);
+    let cells = (0..side)
+        .flat_map(|y| (0..side).map(move |x| (y, x)))
+        .filter(|cell| next[cell.0][cell.1].is_some())
+        .collect::<Vec<_>>();
+
+    let mut rows = (0..side)
+        .map(|_| {
+            (0..side)
+                .map(|_| *rng.choose(b"...|-LJ7F"))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    let mut previous = vec![vec![(0, 0); side]; side];
+    for &cell in &cells {
+        let n = next[cell.0][cell.1].unwrap();
+        previous[n.0][n.1] = cell;
+    }
+    for &cell in &cells {
+        let n = next[cell.0][cell.1].unwrap();
+        let p = previous[cell.0][cell.1];
+        rows[cell.0][cell.1] = pipe(delta(cell, p), delta(cell, n));
+    }
+
+    // Pipes next to the start may not connect to it unless they are part of the loop.
+    let start = *rng.choose(&cells);
+    rows[start.0][start.1] = b'S';
+    for direction in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
+        if let Some(neighbour) = offset(start, direction, side) {
+            if next[neighbour.0][neighbour.1].is_none() {
+                rows[neighbour.0][neighbour.1] = b'.';
+            }
+        }
+    }
+
+    render_grid(&rows)
+}
@@ -0,0 +1,28 @@
+use crate::generate::{render_grid, scaled_side};
+use crate::util::rng::Rng;
+
+pub fn generate(rng: &mut Rng, scale: f64) -> String {
+    let side = scaled_side(140, scale).max(2);
+    // Some rows and columns are left empty to make sure the expansion is exercised.
+    let empty_rows = (0..side).map(|_| rng.chance(0.05)).collect::<Vec<_>>();
+    let empty_columns = (0..side).map(|_| rng.chance(0.05)).collect::<Vec<_>>();
+
+    let mut rows = (0..side)
+        .map(|j| {
+            (0..side)
+                .map(|i| {
+                    if !empty_rows[j] && !empty_columns[i] && rng.chance(0.025) {
+                        b'#'
+                    } else {
+                        b'.'
+                    }
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    if !rows.iter().flatten().any(|c| *c == b'#') {
+        rows[rng.index(side)][rng.index(side)] = b'#';
+    }
+
+    render_grid(&rows)
+}
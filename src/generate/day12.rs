@@ -0,0 +1,35 @@
+use crate::generate::scaled;
+use crate::util::rng::Rng;
+
+pub fn generate(rng: &mut Rng, scale: f64) -> String {
+    (0..scaled(1000, scale))
+        .map(|_| {
+            // Start from a known arrangement so there is at least one solution.
+            let mut springs = (0..rng.range(1..21))
+                .map(|_| rng.chance(0.5))
+                .collect::<Vec<_>>();
+            if !springs.contains(&true) {
+                let i = rng.index(springs.len());
+                springs[i] = true;
+            }
+
+            let groups = springs
+                .split(|broken| !broken)
+                .filter(|group| !group.is_empty())
+                .map(|group| group.len().to_string())
+                .collect::<Vec<_>>()
+                .join(",");
+            let conditions = springs
+                .iter()
+                .map(|broken| match (rng.chance(0.5), *broken) {
+                    (true, _) => '?',
+                    (false, true) => '#',
+                    (false, false) => '.',
+                })
+                .collect::<String>();
+
+            format!("{} {}", conditions, groups)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
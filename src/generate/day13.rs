@@ -0,0 +1,95 @@
+use crate::generate::{render_grid, scaled};
+use crate::util::rng::Rng;
+
+/// Number of differences when mirroring the rows at `line`.
+fn mirror_errors(rows: &[Vec<bool>], line: usize) -> usize {
+    (0..line)
+        .rev()
+        .zip(line..rows.len())
+        .map(|(a, b)| rows[a].iter().zip(&rows[b]).filter(|(a, b)| a != b).count())
+        .sum()
+}
+
+fn transposed(rows: &[Vec<bool>]) -> Vec<Vec<bool>> {
+    (0..rows[0].len())
+        .map(|i| rows.iter().map(|row| row[i]).collect())
+        .collect()
+}
+
+/// Index of the cell that `i` mirrors at `line`, or `i` itself if it is not covered by the mirror.
+fn mirrored(i: usize, line: usize) -> usize {
+    if i >= line && i - line < line {
+        2 * line - 1 - i
+    } else {
+        i
+    }
+}
+
+/// A pattern with a perfect horizontal mirror, and a vertical mirror with a single smudge.
+fn generate_candidate(rng: &mut Rng) -> Vec<Vec<bool>> {
+    let height = rng.range(5..18) as usize;
+    let width = rng.range(5..18) as usize;
+    let row_line = rng.range(1..height as u64) as usize;
+    let column_line = rng.range(1..width as u64) as usize;
+
+    let base = (0..height)
+        .map(|_| (0..width).map(|_| rng.chance(0.5)).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    let mut rows = (0..height)
+        .map(|j| {
+            (0..width)
+                .map(|i| base[mirrored(j, row_line)][mirrored(i, column_line)])
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    // The smudge has to be in a row outside of the horizontal mirror, to keep that one intact.
+    let row_reach = row_line.min(height - row_line);
+    let free_rows = (0..height)
+        .filter(|j| *j + row_reach < row_line || *j >= row_line + row_reach)
+        .collect::<Vec<_>>();
+    if let Some(&j) = free_rows.get(rng.index(free_rows.len().max(1))) {
+        let reach = column_line.min(width - column_line);
+        let i = column_line - reach + rng.index(2 * reach);
+        rows[j][i] = !rows[j][i];
+    }
+    rows
+}
+
+fn generate_pattern(rng: &mut Rng) -> Vec<Vec<bool>> {
+    loop {
+        let mut rows = generate_candidate(rng);
+        if rng.chance(0.5) {
+            rows = transposed(&rows);
+        }
+
+        // Random patterns can contain additional mirrors, the puzzle requires exactly one of each.
+        let columns = transposed(&rows);
+        let errors = (1..rows.len())
+            .map(|line| mirror_errors(&rows, line))
+            .chain((1..columns.len()).map(|line| mirror_errors(&columns, line)))
+            .collect::<Vec<_>>();
+        let perfect = errors.iter().filter(|errors| **errors == 0).count();
+        let smudged = errors.iter().filter(|errors| **errors == 1).count();
+        if perfect == 1 && smudged == 1 {
+            return rows;
+        }
+    }
+}
+
+pub fn generate(rng: &mut Rng, scale: f64) -> String {
+    (0..scaled(100, scale))
+        .map(|_| {
+            let rows = generate_pattern(rng)
+                .into_iter()
+                .map(|row| {
+                    row.into_iter()
+                        .map(|c| if c { b'#' } else { b'.' })
+                        .collect()
+                })
+                .collect::<Vec<_>>();
+            render_grid(&rows)
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
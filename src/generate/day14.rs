@@ -0,0 +1,18 @@
+use crate::generate::{render_grid, scaled_side};
+use crate::util::rng::Rng;
+
+pub fn generate(rng: &mut Rng, scale: f64) -> String {
+    let side = scaled_side(100, scale);
+    let rows = (0..side)
+        .map(|_| {
+            (0..side)
+                .map(|_| match rng.index(100) {
+                    0..=9 => b'#',
+                    10..=29 => b'O',
+                    _ => b'.',
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    render_grid(&rows)
+}
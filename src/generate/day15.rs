@@ -0,0 +1,26 @@
+use crate::generate::scaled;
+use crate::util::rng::Rng;
+
+pub fn generate(rng: &mut Rng, scale: f64) -> String {
+    // Steps draw from a limited set of labels so lenses get replaced and removed.
+    let labels = (0..scaled(500, scale))
+        .map(|_| {
+            (0..rng.range(2..7))
+                .map(|_| char::from(b'a' + rng.index(26) as u8))
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>();
+
+    // There is no trailing newline, it would otherwise end up in the last step.
+    (0..scaled(4000, scale))
+        .map(|_| {
+            let label = rng.choose(&labels);
+            if rng.chance(0.3) {
+                format!("{}-", label)
+            } else {
+                format!("{}={}", label, rng.range(1..10))
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
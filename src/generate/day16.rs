@@ -0,0 +1,21 @@
+use crate::generate::{render_grid, scaled_side};
+use crate::util::rng::Rng;
+
+pub fn generate(rng: &mut Rng, scale: f64) -> String {
+    // The solver packs positions into 7 bits, which limits the size.
+    let side = scaled_side(110, scale).min(127);
+    let rows = (0..side)
+        .map(|_| {
+            (0..side)
+                .map(|_| match rng.index(100) {
+                    0..=2 => b'/',
+                    3..=5 => b'\\',
+                    6..=8 => b'|',
+                    9..=11 => b'-',
+                    _ => b'.',
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    render_grid(&rows)
+}
@@ -0,0 +1,15 @@
+use crate::generate::{render_grid, scaled_side};
+use crate::util::rng::Rng;
+
+pub fn generate(rng: &mut Rng, scale: f64) -> String {
+    // Part 2 moves at least four steps at once, so smaller grids have no path to the end.
+    let side = scaled_side(141, scale).max(5);
+    let rows = (0..side)
+        .map(|_| {
+            (0..side)
+                .map(|_| b'1' + rng.index(9) as u8)
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    render_grid(&rows)
+}
@@ -0,0 +1,119 @@
+//! Random, structurally valid puzzle inputs.
+//!
+//! Every generator takes a `scale` where `1.0` produces an input of roughly the size of the real
+//! puzzle input. The same seed and scale always produce the same input.
+
+use crate::error::Error;
+use crate::util::rng::Rng;
+
+pub mod day01;
+pub mod day02;
+pub mod day03;
+pub mod day04;
+pub mod day05;
+pub mod day06;
+pub mod day07;
+pub mod day08;
+pub mod day09;
+pub mod day10;
+pub mod day11;
+pub mod day12;
+pub mod day13;
+pub mod day14;
+pub mod day15;
+pub mod day16;
+pub mod day17;
+
+const GENERATORS: [fn(&mut Rng, f64) -> String; 17] = [
+    day01::generate,
+    day02::generate,
+    day03::generate,
+    day04::generate,
+    day05::generate,
+    day06::generate,
+    day07::generate,
+    day08::generate,
+    day09::generate,
+    day10::generate,
+    day11::generate,
+    day12::generate,
+    day13::generate,
+    day14::generate,
+    day15::generate,
+    day16::generate,
+    day17::generate,
+];
+
+pub fn generate(day: usize, seed: u64, scale: f64) -> String {
+    try_generate(day, seed, scale).unwrap()
+}
+
+pub fn try_generate(day: usize, seed: u64, scale: f64) -> Result<String, Error> {
+    let generator = day
+        .checked_sub(1)
+        .and_then(|index| GENERATORS.get(index))
+        .ok_or(Error::Invalid("there is no generator for this day"))?;
+    Ok(generator(&mut Rng::from_seed(seed), scale))
+}
+
+/// Number of items for an input which has `count` of them in the real input.
+fn scaled(count: usize, scale: f64) -> usize {
+    ((count as f64 * scale).round() as usize).max(1)
+}
+
+/// Side length of a square grid whose area scales with the input.
+fn scaled_side(side: usize, scale: f64) -> usize {
+    ((side as f64 * scale.sqrt()).round() as usize).max(1)
+}
+
+/// Checks a solution against its reference solver on the inputs generated from the seeds
+/// `0..seeds`, cycling through `scales`.
+#[cfg(test)]
+pub(crate) fn assert_matches_reference<T: PartialEq + std::fmt::Debug>(
+    generator: fn(&mut Rng, f64) -> String,
+    seeds: u64,
+    scales: &[f64],
+    solve: impl Fn(&str) -> T,
+    reference: impl Fn(&str) -> T,
+) {
+    for seed in 0..seeds {
+        let scale = scales[seed as usize % scales.len()];
+        let input = generator(&mut Rng::from_seed(seed), scale);
+        assert_eq!(solve(&input), reference(&input), "seed {}", seed);
+    }
+}
+
+fn render_grid(rows: &[Vec<u8>]) -> String {
+    rows.iter()
+        .map(|row| String::from_utf8(row.clone()).unwrap())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day::DAYS;
+
+    #[test]
+    fn deterministic() {
+        for day in 1..=GENERATORS.len() {
+            assert_eq!(generate(day, 3, 0.1), generate(day, 3, 0.1), "day{}", day);
+        }
+    }
+    #[test]
+    fn unknown_day() {
+        assert!(try_generate(0, 0, 1.0).is_err());
+        assert!(try_generate(GENERATORS.len() + 1, 0, 1.0).is_err());
+    }
+    #[test]
+    fn solvable() {
+        for day in DAYS {
+            for seed in 0..5 {
+                for scale in [0.01, 0.1] {
+                    (day.solve)(&generate(day.number, seed, scale));
+                }
+            }
+        }
+    }
+}
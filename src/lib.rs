@@ -1,4 +1,8 @@
 pub mod day;
-mod solution;
+pub mod error;
+pub mod frames;
+pub mod fuzz;
+pub mod generate;
+pub mod solution;
 #[allow(dead_code)]
 mod util;
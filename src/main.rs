@@ -1,6 +1,15 @@
-use clap::Parser;
-use std::hint::black_box;
-use std::time::Instant;
+#[cfg(feature = "count-allocations")]
+mod allocations;
+mod baseline;
+
+use aoc_2023::day::{day03, day10, day17, Measure, Phase, DAYS};
+use aoc_2023::error::Error;
+use aoc_2023::solution::Solution;
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser, Subcommand};
+use std::fmt::Display;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
@@ -10,58 +19,220 @@ struct Args {
     day: Option<usize>,
     #[arg(short, long, default_value = "1")]
     repeat: u32,
+    /// Store the per-phase timings under this name
+    #[arg(long)]
+    save_baseline: Option<String>,
+    /// Compare the per-phase timings against the baseline with this name
+    #[arg(long)]
+    compare: Option<String>,
+    /// File the baselines are saved in
+    #[arg(long, default_value = "./baselines.json")]
+    baselines: PathBuf,
+    /// Slowdown in percent above which a phase is flagged as a regression
+    #[arg(long, default_value = "5")]
+    threshold: f64,
+    /// Write the frames recorded by the solutions as PPM images into this directory
+    #[cfg(feature = "record-frames")]
+    #[arg(long)]
+    frames: Option<std::path::PathBuf>,
+    /// Play the recorded frames back in the terminal
+    #[cfg(feature = "record-frames")]
+    #[arg(long)]
+    replay: bool,
+    /// Record only every n-th frame
+    #[cfg(feature = "record-frames")]
+    #[arg(long, default_value = "1")]
+    frame_every: usize,
+    /// Stop recording after this many frames per phase
+    #[cfg(feature = "record-frames")]
+    #[arg(long, default_value = "1000")]
+    max_frames: usize,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Print a random input for a day
+    Gen {
+        /// Day
+        #[arg(short, long)]
+        day: usize,
+        #[arg(short, long, default_value = "0")]
+        seed: u64,
+        /// Size relative to the real input
+        #[arg(long, default_value = "1")]
+        scale: f64,
+    },
+    /// Draw a day's input with what the solution found highlighted
+    Render {
+        /// Day
+        #[arg(short, long)]
+        day: usize,
+    },
+}
+
+/// Exits with a usage error, like the ones clap reports for invalid arguments.
+fn exit_with_error(message: impl Display) -> ! {
+    Args::command()
+        .error(ErrorKind::InvalidValue, message)
+        .exit()
 }
 
 fn read_input(day: usize) -> String {
     std::fs::read_to_string(format!("./input/day{:0>2}.txt", day)).unwrap()
 }
 
+struct Timer {
+    repeat: u32,
+    timings: Vec<(Phase, Duration)>,
+    #[cfg(feature = "count-allocations")]
+    allocations: Vec<(Phase, allocations::Allocations)>,
+    #[cfg(feature = "record-frames")]
+    frame_settings: Option<aoc_2023::frames::Settings>,
+    #[cfg(feature = "record-frames")]
+    frames: Vec<aoc_2023::frames::Frame>,
+}
+
+impl Measure for Timer {
+    fn measure(&mut self, phase: Phase, run: &mut dyn FnMut()) {
+        // Counted over a separate run, so the timed runs are not affected by dropping previous results.
+        #[cfg(feature = "count-allocations")]
+        {
+            let allocations = allocations::start();
+            run();
+            self.allocations.push((phase, allocations.finish()));
+        }
+        // Recorded over a separate run as well, taking snapshots is far slower than solving.
+        #[cfg(feature = "record-frames")]
+        if let Some(settings) = self.frame_settings {
+            aoc_2023::frames::start(settings);
+            run();
+            self.frames.extend(aoc_2023::frames::finish());
+        }
+        let start = Instant::now();
+        for _ in 0..self.repeat {
+            run();
+        }
+        let end = Instant::now();
+        self.timings.push((phase, (end - start) / self.repeat));
+    }
+}
+
 fn main() {
     let args = Args::parse();
     assert!(args.repeat > 0);
 
-    let all_days = [
-        aoc_2023::day::day01::solve,
-        aoc_2023::day::day02::solve,
-        aoc_2023::day::day03::solve,
-        aoc_2023::day::day04::solve,
-        aoc_2023::day::day05::solve,
-        aoc_2023::day::day06::solve,
-        aoc_2023::day::day07::solve,
-        aoc_2023::day::day08::solve,
-        aoc_2023::day::day09::solve,
-        aoc_2023::day::day10::solve,
-        aoc_2023::day::day11::solve,
-        aoc_2023::day::day12::solve,
-        aoc_2023::day::day13::solve,
-        aoc_2023::day::day14::solve,
-        aoc_2023::day::day15::solve,
-        aoc_2023::day::day16::solve,
-        aoc_2023::day::day17::solve,
-    ];
+    match args.command {
+        Some(Command::Gen { day, seed, scale }) => {
+            match aoc_2023::generate::try_generate(day, seed, scale) {
+                Ok(input) => print!("{}", input),
+                Err(err) => exit_with_error(err),
+            }
+            return;
+        }
+        Some(Command::Render { day }) => {
+            // Checked before reading the input, which may not exist for other days.
+            let render: fn(&str) -> Result<String, Error> = match day {
+                3 => |input| day03::render(input).map(|renderer| renderer.to_string()),
+                10 => |input| day10::render(input).map(|renderer| renderer.to_string()),
+                17 => |input| day17::render(input).map(|renderer| renderer.to_string()),
+                _ => exit_with_error(format!("day {} can not be rendered", day)),
+            };
+            match render(&read_input(day)) {
+                Ok(rendered) => print!("{}", rendered),
+                Err(err) => exit_with_error(err),
+            }
+            return;
+        }
+        None => {}
+    }
 
-    let day_and_solver: Vec<_> = match args.day {
-        None => all_days
-            .into_iter()
-            .enumerate()
-            .map(|(i, solve)| (i + 1, solve, read_input(i + 1)))
-            .collect(),
-        Some(d) => vec![(d, all_days[d - 1], read_input(d))],
+    // Checked up front, so a corrupt file or a mistyped name does not only show up after all days
+    // have run.
+    let baselines = match (&args.compare, &args.save_baseline) {
+        (None, None) => baseline::Baselines::new(),
+        _ => baseline::load_all(&args.baselines).unwrap_or_else(|err| exit_with_error(err)),
     };
+    let compare_baseline = args.compare.as_ref().map(|name| {
+        baselines.get(name).unwrap_or_else(|| {
+            exit_with_error(format!(
+                "baseline {} not found in {}",
+                name,
+                args.baselines.display()
+            ))
+        })
+    });
 
-    let start = Instant::now();
-    day_and_solver.into_iter().for_each(|(day, solver, input)| {
-        let start = Instant::now();
-        for _ in 0..args.repeat - 1 {
-            black_box(solver(black_box(&input)));
+    let days = match args.day {
+        None => &DAYS[..],
+        Some(d) => &DAYS[d - 1..d],
+    };
+    let day_and_input: Vec<_> = days
+        .iter()
+        .map(|day| (day, read_input(day.number)))
+        .collect();
+
+    let mut timings = baseline::Timings::new();
+    let mut total = Duration::ZERO;
+    day_and_input.into_iter().for_each(|(day, input)| {
+        let mut timer = Timer {
+            repeat: args.repeat,
+            timings: vec![],
+            #[cfg(feature = "count-allocations")]
+            allocations: vec![],
+            #[cfg(feature = "record-frames")]
+            frame_settings: (args.frames.is_some() || args.replay).then_some(
+                aoc_2023::frames::Settings {
+                    every: args.frame_every,
+                    max_frames: args.max_frames,
+                },
+            ),
+            #[cfg(feature = "record-frames")]
+            frames: vec![],
+        };
+        let (p1, p2) = (day.phases)(&input, &mut timer);
+
+        for (part, solution) in [(1, p1), (2, p2)] {
+            println!("day{}/part{}: {}", day.number, part, solution);
+            if let Solution::Grid(grid) = solution {
+                print!("{}", grid);
+            }
+        }
+        let solve_time = timer.timings.iter().map(|(_, duration)| *duration).sum();
+        println!("day{}/solve_time: {:?}", day.number, solve_time);
+        #[cfg(feature = "count-allocations")]
+        for (phase, allocations) in &timer.allocations {
+            println!("day{}/{}_allocations: {}", day.number, phase, allocations);
+        }
+        #[cfg(feature = "record-frames")]
+        {
+            if let Some(directory) = &args.frames {
+                aoc_2023::frames::write_ppm_sequence(&timer.frames, directory, 4).unwrap();
+            }
+            if args.replay {
+                let delay = Duration::from_millis(50);
+                aoc_2023::frames::replay(&timer.frames, &mut std::io::stdout(), delay).unwrap();
+            }
+            println!("day{}/frames: {}", day.number, timer.frames.len());
         }
-        let (p1, p2) = solver(black_box(&input));
-        let end = Instant::now();
 
-        println!("day{}/part1: {}", day, p1);
-        println!("day{}/part2: {}", day, p2);
-        println!("day{}/solve_time: {:?}", day, (end - start) / args.repeat);
+        total += solve_time;
+        for (phase, duration) in timer.timings {
+            baseline::insert(&mut timings, day.number, phase, duration);
+        }
     });
-    let end = Instant::now();
-    println!("Total solve_time: {:?}", (end - start) / args.repeat);
+    println!("Total solve_time: {:?}", total);
+
+    if let (Some(name), Some(baseline)) = (&args.compare, compare_baseline) {
+        let regressions = baseline::compare(baseline, &timings, args.threshold);
+        println!(
+            "{} regression(s) above {}% compared to {}",
+            regressions, args.threshold, name
+        );
+    }
+    if let Some(name) = &args.save_baseline {
+        baseline::save(&args.baselines, baselines, name, &timings)
+            .unwrap_or_else(|err| exit_with_error(err));
+    }
 }
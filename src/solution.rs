@@ -1,5 +1,7 @@
 // Copy from https://github.com/agubelu/AoC-rust-template/tree/master
 
+use crate::util::grid::Grid;
+use crate::util::ocr::recognise;
 use std::fmt::{Display, Formatter, Result};
 use Solution::*;
 
@@ -18,6 +20,7 @@ pub enum Solution {
     U128(u128),
     Usize(usize),
     Str(String),
+    Grid(Grid<bool>),
 }
 
 impl Display for Solution {
@@ -36,6 +39,10 @@ impl Display for Solution {
             U128(x) => x.fmt(f),
             Usize(x) => x.fmt(f),
             Str(x) => x.fmt(f),
+            Grid(x) => match recognise(x) {
+                Some(letters) => letters.fmt(f),
+                None => f.write_str("<unrecognised>"),
+            },
         }
     }
 }
@@ -64,6 +71,12 @@ impl_from!(u128, U128);
 impl_from!(usize, Usize);
 impl_from!(String, Str);
 
+impl From<Grid<bool>> for Solution {
+    fn from(sol: Grid<bool>) -> Self {
+        Self::Grid(sol)
+    }
+}
+
 impl From<&str> for Solution {
     fn from(sol: &str) -> Self {
         Self::Str(sol.to_owned())
@@ -0,0 +1,356 @@
+use crate::util::grid::{Grid, GridView};
+use crate::util::position::{Dimensions, Position};
+use std::fmt::{Display, Formatter, Write};
+use std::ops::{BitAndAssign, BitOrAssign, BitXorAssign};
+
+/// A grid of booleans packed into `u64` words, each row starting at a new word.
+///
+/// Bit `x % 64` of word `x / 64` of a row holds column `x`. Bits past the width are always unset,
+/// so rows can be compared and counted word by word.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct BitGrid {
+    pub dimensions: Dimensions,
+    words_per_row: usize,
+    words: Vec<u64>,
+}
+
+impl BitGrid {
+    pub fn from_dimensions(dimensions: Dimensions) -> Self {
+        let words_per_row = dimensions.width().div_ceil(64);
+        Self {
+            dimensions,
+            words_per_row,
+            words: vec![0; dimensions.height() * words_per_row],
+        }
+    }
+
+    pub fn from_view(view: &impl GridView<Cell = bool>) -> Self {
+        let mut grid = Self::from_dimensions(view.dimensions());
+        for (pos, value) in view.iter() {
+            if *value {
+                grid.insert(&pos);
+            }
+        }
+        grid
+    }
+
+    #[inline]
+    fn word_and_mask(&self, pos: &Position) -> (usize, u64) {
+        debug_assert!(self.dimensions.contains(pos));
+        (
+            pos.y() * self.words_per_row + pos.x() / 64,
+            1 << (pos.x() % 64),
+        )
+    }
+
+    #[inline]
+    pub fn get(&self, pos: &Position) -> bool {
+        let (word, mask) = self.word_and_mask(pos);
+        self.words[word] & mask != 0
+    }
+
+    #[inline]
+    pub fn set(&mut self, pos: &Position, value: bool) {
+        let (word, mask) = self.word_and_mask(pos);
+        match value {
+            true => self.words[word] |= mask,
+            false => self.words[word] &= !mask,
+        }
+    }
+
+    /// Sets the cell, returning whether it was unset before.
+    #[inline]
+    pub fn insert(&mut self, pos: &Position) -> bool {
+        let (word, mask) = self.word_and_mask(pos);
+        let inserted = self.words[word] & mask == 0;
+        self.words[word] |= mask;
+        inserted
+    }
+
+    pub fn clear(&mut self) {
+        self.words.fill(0);
+    }
+
+    pub fn count(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    /// Whether any set cell lies on the edge of the grid.
+    pub fn touches_border(&self) -> bool {
+        let (height, width) = (self.dimensions.height(), self.dimensions.width());
+        if height == 0 || width == 0 {
+            return false;
+        }
+        let edge_column = |y| self.get(&Position(y, 0)) || self.get(&Position(y, width - 1));
+        self.count_row(0) > 0 || self.count_row(height - 1) > 0 || (0..height).any(edge_column)
+    }
+
+    pub fn row(&self, y: usize) -> &[u64] {
+        &self.words[y * self.words_per_row..(y + 1) * self.words_per_row]
+    }
+
+    pub fn count_row(&self, y: usize) -> usize {
+        self.row(y)
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    pub fn rows_equal(&self, a: usize, b: usize) -> bool {
+        self.row(a) == self.row(b)
+    }
+
+    /// Number of columns in which rows `a` and `b` differ.
+    pub fn row_mismatches(&self, a: usize, b: usize) -> usize {
+        self.row(a)
+            .iter()
+            .zip(self.row(b))
+            .map(|(a, b)| (a ^ b).count_ones() as usize)
+            .sum()
+    }
+
+    /// The set cells, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = Position> + '_ {
+        self.words.iter().enumerate().flat_map(move |(i, &word)| {
+            let y = i / self.words_per_row;
+            let x_offset = (i % self.words_per_row) * 64;
+            let mut remaining = word;
+            std::iter::from_fn(move || {
+                if remaining == 0 {
+                    return None;
+                }
+                let bit = remaining.trailing_zeros() as usize;
+                remaining &= remaining - 1;
+                Some(Position(y, x_offset + bit))
+            })
+        })
+    }
+
+    pub fn transposed(&self) -> Self {
+        let mut transposed = Self::from_dimensions(self.dimensions.transposed());
+        for pos in self.iter() {
+            transposed.insert(&Position(pos.x(), pos.y()));
+        }
+        transposed
+    }
+
+    /// Moves every cell `dx` columns to the right, or to the left if negative. Cells moved past
+    /// the edge are dropped.
+    pub fn shift_columns(&mut self, dx: isize) {
+        let distance = dx.unsigned_abs();
+        let (word_shift, bit_shift) = (distance / 64, distance % 64);
+        let words_per_row = self.words_per_row;
+        for row in self.words.chunks_exact_mut(words_per_row.max(1)) {
+            if dx >= 0 {
+                for i in (0..words_per_row).rev() {
+                    let source = |j: Option<usize>| j.map_or(0, |j| row[j]);
+                    let high = source(i.checked_sub(word_shift));
+                    let low = source(i.checked_sub(word_shift + 1));
+                    row[i] = match bit_shift {
+                        0 => high,
+                        _ => high << bit_shift | low >> (64 - bit_shift),
+                    };
+                }
+            } else {
+                for i in 0..words_per_row {
+                    let source = |j: usize| row.get(j).copied().unwrap_or(0);
+                    let low = source(i + word_shift);
+                    let high = source(i + word_shift + 1);
+                    row[i] = match bit_shift {
+                        0 => low,
+                        _ => low >> bit_shift | high << (64 - bit_shift),
+                    };
+                }
+            }
+        }
+        self.clear_past_width();
+    }
+
+    /// Moves every cell `dy` rows down, or up if negative. Cells moved past the edge are dropped.
+    pub fn shift_rows(&mut self, dy: isize) {
+        let distance = (dy.unsigned_abs() * self.words_per_row).min(self.words.len());
+        if dy >= 0 {
+            self.words.rotate_right(distance);
+            self.words[..distance].fill(0);
+        } else {
+            self.words.rotate_left(distance);
+            let len = self.words.len();
+            self.words[len - distance..].fill(0);
+        }
+    }
+
+    fn clear_past_width(&mut self) {
+        let used_bits = self.dimensions.width() % 64;
+        if used_bits == 0 {
+            return;
+        }
+        let mask = (1 << used_bits) - 1;
+        for row in self.words.chunks_exact_mut(self.words_per_row) {
+            row[self.words_per_row - 1] &= mask;
+        }
+    }
+
+    fn combine(&mut self, other: &Self, f: impl Fn(&mut u64, u64)) {
+        assert_eq!(self.dimensions, other.dimensions);
+        self.words
+            .iter_mut()
+            .zip(&other.words)
+            .for_each(|(word, &other)| f(word, other));
+    }
+}
+
+/// Cells are returned as references to constants, the inherent `get` returns them by value.
+impl GridView for BitGrid {
+    type Cell = bool;
+
+    fn dimensions(&self) -> Dimensions {
+        self.dimensions
+    }
+
+    fn get(&self, pos: &Position) -> &bool {
+        match BitGrid::get(self, pos) {
+            true => &true,
+            false => &false,
+        }
+    }
+}
+
+impl BitAndAssign<&BitGrid> for BitGrid {
+    fn bitand_assign(&mut self, rhs: &BitGrid) {
+        self.combine(rhs, |word, other| *word &= other);
+    }
+}
+
+impl BitOrAssign<&BitGrid> for BitGrid {
+    fn bitor_assign(&mut self, rhs: &BitGrid) {
+        self.combine(rhs, |word, other| *word |= other);
+    }
+}
+
+impl BitXorAssign<&BitGrid> for BitGrid {
+    fn bitxor_assign(&mut self, rhs: &BitGrid) {
+        self.combine(rhs, |word, other| *word ^= other);
+    }
+}
+
+impl From<&Grid<bool>> for BitGrid {
+    fn from(value: &Grid<bool>) -> Self {
+        let mut grid = Self::from_dimensions(value.dimensions);
+        for (row, words) in value
+            .row_slices()
+            .zip(grid.words.chunks_exact_mut(grid.words_per_row.max(1)))
+        {
+            for (cells, word) in row.chunks(64).zip(words) {
+                *word = cells
+                    .iter()
+                    .rev()
+                    .fold(0, |word, &cell| word << 1 | cell as u64);
+            }
+        }
+        grid
+    }
+}
+
+impl From<&BitGrid> for Grid<bool> {
+    fn from(value: &BitGrid) -> Self {
+        let mut grid = Grid::from_dimensions(value.dimensions, false);
+        grid.extend(value.iter());
+        grid
+    }
+}
+
+impl Display for BitGrid {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        (0..self.dimensions.height()).try_for_each(|y| {
+            (0..self.dimensions.width()).try_for_each(|x| {
+                f.write_char(match self.get(&Position(y, x)) {
+                    true => '█',
+                    false => ' ',
+                })
+            })?;
+            f.write_char('\n')
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::rng::Rng;
+
+    fn random_grid(rng: &mut Rng, height: usize, width: usize) -> Grid<bool> {
+        Grid::from_rows(
+            (0..height).map(|_| (0..width).map(|_| rng.chance(0.5)).collect::<Vec<_>>()),
+        )
+    }
+
+    #[test]
+    fn conversions() {
+        let mut rng = Rng::from_seed(0);
+        let grid = random_grid(&mut rng, 5, 130);
+        let bits = BitGrid::from(&grid);
+        assert_eq!(bits.count(), grid.count());
+        assert_eq!(Grid::from(&bits), grid);
+        assert_eq!(Grid::from(&bits.transposed()), grid.transposed());
+        assert_eq!(BitGrid::from_view(&grid.transpose()), bits.transposed());
+        assert_eq!(bits.to_grid(), grid);
+        assert_eq!(
+            bits.count_row(3),
+            grid.get_row(3).iter().filter(|v| **v).count()
+        );
+        assert_eq!(
+            bits.row_mismatches(1, 2),
+            (0..130).filter(|&x| grid[1][x] != grid[2][x]).count()
+        );
+        assert!(bits.rows_equal(4, 4));
+    }
+    #[test]
+    fn operators() {
+        let mut rng = Rng::from_seed(1);
+        let a = random_grid(&mut rng, 3, 70);
+        let b = random_grid(&mut rng, 3, 70);
+        let combined = |f: fn(bool, bool) -> bool| {
+            Grid::from_rows(
+                (0..3)
+                    .map(|y| (0..70).map(move |x| (y, x)))
+                    .map(|row| row.map(|(y, x)| f(a[y][x], b[y][x])).collect::<Vec<_>>()),
+            )
+        };
+        let mut and = BitGrid::from(&a);
+        and &= &BitGrid::from(&b);
+        assert_eq!(Grid::from(&and), combined(|a, b| a & b));
+        let mut or = BitGrid::from(&a);
+        or |= &BitGrid::from(&b);
+        assert_eq!(Grid::from(&or), combined(|a, b| a | b));
+        let mut xor = BitGrid::from(&a);
+        xor ^= &BitGrid::from(&b);
+        assert_eq!(Grid::from(&xor), combined(|a, b| a ^ b));
+    }
+    #[test]
+    fn shifts() {
+        let mut rng = Rng::from_seed(2);
+        let grid = random_grid(&mut rng, 4, 150);
+        let (height, width) = (4isize, 150isize);
+        for dy in [-5, -1, 0, 2] {
+            for dx in [-150, -70, -64, -3, 0, 1, 63, 64, 100] {
+                let mut bits = BitGrid::from(&grid);
+                bits.shift_rows(dy);
+                bits.shift_columns(dx);
+                let expected = Grid::from_rows((0..height).map(|y| {
+                    (0..width)
+                        .map(|x| {
+                            let (y, x) = (y - dy, x - dx);
+                            (0..height).contains(&y)
+                                && (0..width).contains(&x)
+                                && grid[y as usize][x as usize]
+                        })
+                        .collect::<Vec<_>>()
+                }));
+                assert_eq!(Grid::from(&bits), expected, "dy {} dx {}", dy, dx);
+            }
+        }
+    }
+}
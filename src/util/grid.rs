@@ -1,84 +1,243 @@
-use crate::util::position::Position;
+use crate::error::Error;
+use crate::util::bitgrid::BitGrid;
+use crate::util::position::{Dimensions, Direction, Position, NEIGHBOURS4, NEIGHBOURS8};
 use num::integer::div_rem;
 use std::fmt::{Display, Formatter, Write};
 use std::ops::{Index, IndexMut};
 
-#[derive(Clone, Eq, PartialEq, Hash)]
+/// The position of the `i`-th cell of data stored row by row, `stride` cells per row.
+fn position_at(i: usize, stride: usize) -> Position {
+    let (y, x) = div_rem(i, stride);
+    Position(y, x)
+}
+
+/// Read access shared by owned grids, grids backed by the input and views into either, so that
+/// algorithms only need to be written once.
+pub trait GridView {
+    type Cell;
+
+    fn dimensions(&self) -> Dimensions;
+
+    /// The cell at `pos`, which has to be inside the grid.
+    fn get(&self, pos: &Position) -> &Self::Cell;
+
+    fn in_bounds(&self, pos: &Position) -> bool {
+        self.dimensions().contains(pos)
+    }
+
+    fn get_checked(&self, pos: &Position) -> Option<&Self::Cell> {
+        self.in_bounds(pos).then(|| self.get(pos))
+    }
+
+    fn offset_position(&self, pos: &Position, dy: isize, dx: isize) -> Option<Position> {
+        pos.checked_offset(&self.dimensions(), dy, dx)
+    }
+
+    fn get_offset(&self, pos: &Position, dy: isize, dx: isize) -> Option<&Self::Cell> {
+        self.offset_position(pos, dy, dx).map(|pos| self.get(&pos))
+    }
+
+    /// The orthogonal neighbours inside the grid, clockwise from up.
+    fn neighbours4(&self, pos: &Position) -> impl Iterator<Item = (Position, &Self::Cell)> + '_ {
+        let pos = *pos;
+        NEIGHBOURS4.into_iter().filter_map(move |(dy, dx)| {
+            let next = self.offset_position(&pos, dy, dx)?;
+            Some((next, self.get(&next)))
+        })
+    }
+
+    /// The orthogonal and diagonal neighbours inside the grid, clockwise from up.
+    fn neighbours8(&self, pos: &Position) -> impl Iterator<Item = (Position, &Self::Cell)> + '_ {
+        let pos = *pos;
+        NEIGHBOURS8.into_iter().filter_map(move |(dy, dx)| {
+            let next = self.offset_position(&pos, dy, dx)?;
+            Some((next, self.get(&next)))
+        })
+    }
+
+    /// The cells of row `y`, which has to be inside the grid.
+    fn row(&self, y: usize) -> impl DoubleEndedIterator<Item = &Self::Cell> + '_ {
+        assert!(y < self.dimensions().height());
+        (0..self.dimensions().width()).map(move |x| self.get(&Position(y, x)))
+    }
+
+    fn rows(&self) -> impl Iterator<Item = impl DoubleEndedIterator<Item = &Self::Cell>> + '_ {
+        (0..self.dimensions().height()).map(|y| self.row(y))
+    }
+
+    /// The cells of column `x`, which has to be inside the grid.
+    fn column(&self, x: usize) -> impl DoubleEndedIterator<Item = &Self::Cell> + '_ {
+        assert!(x < self.dimensions().width());
+        (0..self.dimensions().height()).map(move |y| self.get(&Position(y, x)))
+    }
+
+    fn columns(&self) -> impl Iterator<Item = impl DoubleEndedIterator<Item = &Self::Cell>> + '_ {
+        (0..self.dimensions().width()).map(|x| self.column(x))
+    }
+
+    fn positions(&self) -> impl DoubleEndedIterator<Item = Position> + '_ {
+        self.dimensions().positions()
+    }
+
+    fn iter(&self) -> impl DoubleEndedIterator<Item = (Position, &Self::Cell)> + '_ {
+        self.positions().map(|pos| (pos, self.get(&pos)))
+    }
+
+    /// A view of `dimensions` cells starting at `top_left`, which has to fit inside the grid.
+    fn view(&self, top_left: Position, dimensions: Dimensions) -> SubGrid<'_, Self>
+    where
+        Self: Sized,
+    {
+        let (height, width) = (self.dimensions().height(), self.dimensions().width());
+        assert!(top_left.y() + dimensions.height() <= height);
+        assert!(top_left.x() + dimensions.width() <= width);
+        SubGrid {
+            grid: self,
+            top_left,
+            dimensions,
+        }
+    }
+
+    fn transform(&self, transform: Transform) -> Transformed<'_, Self>
+    where
+        Self: Sized,
+    {
+        Transformed {
+            grid: self,
+            transform,
+        }
+    }
+
+    /// A view rotated clockwise by a quarter turn.
+    fn rotate90(&self) -> Transformed<'_, Self>
+    where
+        Self: Sized,
+    {
+        self.transform(Transform::ROTATE90)
+    }
+
+    fn rotate180(&self) -> Transformed<'_, Self>
+    where
+        Self: Sized,
+    {
+        self.transform(Transform::ROTATE180)
+    }
+
+    fn rotate270(&self) -> Transformed<'_, Self>
+    where
+        Self: Sized,
+    {
+        self.transform(Transform::ROTATE270)
+    }
+
+    /// A view mirrored left to right.
+    fn flip_horizontal(&self) -> Transformed<'_, Self>
+    where
+        Self: Sized,
+    {
+        self.transform(Transform::FLIP_HORIZONTAL)
+    }
+
+    /// A view mirrored top to bottom.
+    fn flip_vertical(&self) -> Transformed<'_, Self>
+    where
+        Self: Sized,
+    {
+        self.transform(Transform::FLIP_VERTICAL)
+    }
+
+    /// A view mirrored along the main diagonal.
+    fn transpose(&self) -> Transformed<'_, Self>
+    where
+        Self: Sized,
+    {
+        self.transform(Transform::TRANSPOSE)
+    }
+
+    /// Copies the cells into an owned grid.
+    fn to_grid(&self) -> Grid<Self::Cell>
+    where
+        Self::Cell: Clone,
+    {
+        Grid {
+            dimensions: self.dimensions(),
+            data: self.iter().map(|(_, cell)| cell.clone()).collect(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
 pub struct Grid<T> {
-    pub dimensions: (usize, usize),
+    pub dimensions: Dimensions,
     data: Vec<T>,
 }
 
 impl<T> Grid<T> {
-    pub fn from_dimensions(dimensions: (usize, usize), value: T) -> Self
+    pub fn from_dimensions(dimensions: Dimensions, value: T) -> Self
     where
         T: Clone,
     {
         Self {
             dimensions,
-            data: vec![value; dimensions.0 * dimensions.1],
+            data: vec![value; dimensions.area()],
         }
     }
     pub fn from_rows<Rows, Cells>(rows: Rows) -> Self
+    where
+        Rows: IntoIterator<Item = Cells>,
+        Cells: IntoIterator<Item = T>,
+    {
+        Self::try_from_rows(rows).unwrap()
+    }
+
+    /// Like [`Grid::from_rows`], but reports the first row with a different length than the first.
+    pub fn try_from_rows<Rows, Cells>(rows: Rows) -> Result<Self, GridParseError>
     where
         Rows: IntoIterator<Item = Cells>,
         Cells: IntoIterator<Item = T>,
     {
         let mut data = vec![];
         let mut width = None;
+        let mut height = 0;
 
         for row in rows {
+            let start = data.len();
             data.extend(row);
-            width.get_or_insert(data.len());
-        }
-
-        let width = match width {
-            None => {
-                return Self {
-                    dimensions: (0, 0),
-                    data,
-                }
+            let length = data.len() - start;
+            let expected = *width.get_or_insert(length);
+            if length != expected {
+                return Err(GridParseError::RaggedRow {
+                    row: height + 1,
+                    length,
+                    expected,
+                });
             }
-            Some(width) => width,
-        };
-
-        assert_eq!(data.len() % width, 0);
+            height += 1;
+        }
 
-        Self {
-            dimensions: (data.len() / width, width),
+        Ok(Self {
+            dimensions: Dimensions::from_height_width(height, width.unwrap_or(0)),
             data,
-        }
+        })
     }
 
     pub fn size(&self) -> usize {
-        self.dimensions.0 * self.dimensions.1
-    }
-
-    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (Position, &T)> + '_ {
-        self.data
-            .iter()
-            .enumerate()
-            .map(|(i, value)| (div_rem(i, self.dimensions.1).into(), value))
+        self.dimensions.area()
     }
 
     pub fn iter_mut(&mut self) -> impl Iterator<Item = (Position, &mut T)> + '_ {
         self.data
             .iter_mut()
             .enumerate()
-            .map(|(i, value)| (div_rem(i, self.dimensions.1).into(), value))
+            .map(|(i, value)| (position_at(i, self.dimensions.width()), value))
     }
 
     pub fn values(&self) -> impl Iterator<Item = &T> + '_ {
         self.data.iter()
     }
 
-    pub fn positions(&self) -> impl DoubleEndedIterator<Item = Position> {
-        let dimensions = self.dimensions;
-        (0..self.data.len()).map(move |i| div_rem(i, dimensions.1).into())
-    }
-
     fn index(&self, pos: &Position) -> usize {
-        pos.0 * self.dimensions.1 + pos.1
+        pos.0 * self.dimensions.width() + pos.1
     }
 
     #[inline]
@@ -97,58 +256,162 @@ impl<T> Grid<T> {
         self.data[idx] = value;
     }
 
-    pub fn rows(&self) -> impl Iterator<Item = &[T]> + '_ {
-        self.data.chunks_exact(self.dimensions.1)
+    /// The rows as slices, unlike [`GridView::rows`].
+    pub fn row_slices(&self) -> impl Iterator<Item = &[T]> + '_ {
+        (0..self.dimensions.height()).map(|j| self.get_row(j))
     }
 
     pub fn get_row(&self, j: usize) -> &[T] {
-        &self.data[j * self.dimensions.1..(j + 1) * self.dimensions.1]
+        &self.data[j * self.dimensions.width()..(j + 1) * self.dimensions.width()]
+    }
+
+    /// The cells of column `i`, stepping through the rows without copying.
+    pub fn get_column(&self, i: usize) -> impl DoubleEndedIterator<Item = &T> + ExactSizeIterator {
+        assert!(i < self.dimensions.width());
+        // Without rows there is no data to start the column in.
+        let data = self.data.get(i..).unwrap_or_default();
+        data.iter().step_by(self.dimensions.width())
+    }
+
+    pub fn get_column_mut(
+        &mut self,
+        i: usize,
+    ) -> impl DoubleEndedIterator<Item = &mut T> + ExactSizeIterator {
+        assert!(i < self.dimensions.width());
+        let width = self.dimensions.width();
+        let data = self.data.get_mut(i..).unwrap_or_default();
+        data.iter_mut().step_by(width)
+    }
+
+    pub fn columns_equal(&self, a: usize, b: usize) -> bool
+    where
+        T: PartialEq,
+    {
+        self.get_column(a).eq(self.get_column(b))
+    }
+
+    /// Number of rows in which columns `a` and `b` differ.
+    pub fn column_mismatches(&self, a: usize, b: usize) -> usize
+    where
+        T: PartialEq,
+    {
+        self.get_column(a)
+            .zip(self.get_column(b))
+            .filter(|(a, b)| a != b)
+            .count()
     }
 
     pub fn transposed(&self) -> Self
     where
-        T: Copy,
+        T: Clone,
     {
-        Grid::from_rows(
-            (0..self.dimensions.1)
-                .map(|i| (0..self.dimensions.0).map(move |j| self.data[j * self.dimensions.1 + i])),
-        )
+        self.transpose().to_grid()
+    }
+
+    pub fn rotated90(&self) -> Self
+    where
+        T: Clone,
+    {
+        self.rotate90().to_grid()
+    }
+
+    pub fn rotated180(&self) -> Self
+    where
+        T: Clone,
+    {
+        self.rotate180().to_grid()
+    }
+
+    pub fn rotated270(&self) -> Self
+    where
+        T: Clone,
+    {
+        self.rotate270().to_grid()
+    }
+
+    pub fn flipped_horizontal(&self) -> Self
+    where
+        T: Clone,
+    {
+        self.flip_horizontal().to_grid()
+    }
+
+    pub fn flipped_vertical(&self) -> Self
+    where
+        T: Clone,
+    {
+        self.flip_vertical().to_grid()
     }
 }
+
+impl<T> GridView for Grid<T> {
+    type Cell = T;
+
+    fn dimensions(&self) -> Dimensions {
+        self.dimensions
+    }
+
+    #[inline]
+    fn get(&self, pos: &Position) -> &T {
+        Grid::get(self, pos)
+    }
+
+    // The rest walks the data directly instead of looking up every position.
+
+    fn row(&self, y: usize) -> impl DoubleEndedIterator<Item = &T> + '_ {
+        self.get_row(y).iter()
+    }
+
+    fn column(&self, x: usize) -> impl DoubleEndedIterator<Item = &T> + '_ {
+        self.get_column(x)
+    }
+
+    fn positions(&self) -> impl DoubleEndedIterator<Item = Position> + '_ {
+        let dimensions = self.dimensions;
+        (0..self.data.len()).map(move |i| position_at(i, dimensions.width()))
+    }
+
+    fn iter(&self) -> impl DoubleEndedIterator<Item = (Position, &T)> + '_ {
+        self.data
+            .iter()
+            .enumerate()
+            .map(|(i, value)| (position_at(i, self.dimensions.width()), value))
+    }
+}
+
 impl<T> Index<usize> for Grid<T> {
     type Output = [T];
 
     #[inline]
     fn index(&self, index: usize) -> &Self::Output {
-        &self.data[index * self.dimensions.1..(index + 1) * self.dimensions.1]
+        &self.data[index * self.dimensions.width()..(index + 1) * self.dimensions.width()]
     }
 }
 
 impl<T> IndexMut<usize> for Grid<T> {
     #[inline]
     fn index_mut(&mut self, index: usize) -> &mut Self::Output {
-        &mut self.data[index * self.dimensions.1..(index + 1) * self.dimensions.1]
+        &mut self.data[index * self.dimensions.width()..(index + 1) * self.dimensions.width()]
     }
 }
 
-impl<A> Extend<((usize, usize), A)> for Grid<A> {
-    fn extend<T: IntoIterator<Item = ((usize, usize), A)>>(&mut self, iter: T) {
+impl<A> Extend<(Position, A)> for Grid<A> {
+    fn extend<T: IntoIterator<Item = (Position, A)>>(&mut self, iter: T) {
         iter.into_iter().for_each(|(position, value)| {
-            self[position.0][position.1] = value;
+            self.set(&position, value);
         });
     }
 }
 
 impl Grid<bool> {
-    pub fn from_points<I: Iterator<Item = (usize, usize)> + Clone>(iter: I) -> Self {
-        let mut max_x = usize::MIN;
-        let mut max_y = usize::MIN;
-        for (y, x) in iter.clone() {
-            max_x = max_x.max(x);
-            max_y = max_y.max(y);
+    pub fn from_points<I: Iterator<Item = Position> + Clone>(iter: I) -> Self {
+        let (mut height, mut width) = (0, 0);
+        for pos in iter.clone() {
+            height = height.max(pos.y() + 1);
+            width = width.max(pos.x() + 1);
         }
 
-        let mut grid = Self::from_dimensions((max_y, max_x), false);
+        let mut grid = Self::from_dimensions(Dimensions::from_height_width(height, width), false);
         grid.extend(iter.map(|pos| (pos, true)));
         grid
     }
@@ -163,6 +426,259 @@ impl Grid<bool> {
     }
 }
 
+/// Which neighbours of a cell count as connected to it.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Connectivity {
+    Four,
+    Eight,
+}
+
+impl Connectivity {
+    fn offsets(self) -> &'static [(isize, isize)] {
+        match self {
+            Connectivity::Four => &NEIGHBOURS4,
+            Connectivity::Eight => &NEIGHBOURS8,
+        }
+    }
+}
+
+/// Depth-first search from each seed in turn, `enter` marks a position as visited and returns
+/// whether the search continues through it.
+fn fill<G: GridView>(
+    grid: &G,
+    seeds: impl IntoIterator<Item = Position>,
+    connectivity: Connectivity,
+    mut enter: impl FnMut(Position) -> bool,
+) {
+    let mut stack = vec![];
+    for seed in seeds {
+        if !enter(seed) {
+            continue;
+        }
+        stack.push(seed);
+        while let Some(pos) = stack.pop() {
+            for &(dy, dx) in connectivity.offsets() {
+                if let Some(next) = grid.offset_position(&pos, dy, dx) {
+                    if enter(next) {
+                        stack.push(next);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The cells connected to any of the `seeds` through cells for which `predicate` holds. Seeds for
+/// which it does not hold are ignored.
+pub fn flood_fill<G: GridView>(
+    grid: &G,
+    seeds: impl IntoIterator<Item = Position>,
+    connectivity: Connectivity,
+    predicate: impl Fn(&Position, &G::Cell) -> bool,
+) -> BitGrid {
+    let mut filled = BitGrid::from_dimensions(grid.dimensions());
+    fill(grid, seeds, connectivity, |pos| {
+        predicate(&pos, grid.get(&pos)) && filled.insert(&pos)
+    });
+    filled
+}
+
+/// Connected regions of a grid, see [`label_components`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Components {
+    /// The label of every cell, where 0 marks cells outside of all components.
+    pub labels: Grid<u32>,
+    /// The number of cells with each label, so `sizes[0]` counts the cells outside of components.
+    pub sizes: Vec<usize>,
+}
+
+impl Components {
+    pub fn count(&self) -> usize {
+        self.sizes.len() - 1
+    }
+
+    /// Whether each label has a cell on the edge of the grid.
+    pub fn touching_border(&self) -> Vec<bool> {
+        let (height, width) = (
+            self.labels.dimensions.height(),
+            self.labels.dimensions.width(),
+        );
+        let mut touching = vec![false; self.sizes.len()];
+        for (pos, &label) in self.labels.iter() {
+            if pos.y() == 0 || pos.x() == 0 || pos.y() == height - 1 || pos.x() == width - 1 {
+                touching[label as usize] = true;
+            }
+        }
+        touching
+    }
+}
+
+/// Labels the connected regions of cells for which `predicate` holds, numbered from 1 in the order
+/// their first cell appears row by row.
+pub fn label_components<G: GridView>(
+    grid: &G,
+    connectivity: Connectivity,
+    predicate: impl Fn(&Position, &G::Cell) -> bool,
+) -> Components {
+    let mut labels = Grid::from_dimensions(grid.dimensions(), 0);
+    let mut sizes = vec![0];
+    for pos in grid.positions() {
+        if *labels.get(&pos) != 0 || !predicate(&pos, grid.get(&pos)) {
+            continue;
+        }
+        let label = sizes.len() as u32;
+        let mut size = 0;
+        fill(grid, [pos], connectivity, |pos| {
+            let entered = *labels.get(&pos) == 0 && predicate(&pos, grid.get(&pos));
+            if entered {
+                labels.set(&pos, label);
+                size += 1;
+            }
+            entered
+        });
+        sizes.push(size);
+    }
+    sizes[0] = labels.size() - sizes.iter().sum::<usize>();
+    Components { labels, sizes }
+}
+
+/// Checks that the lines of the input form a non-empty rectangle of cells accepted by `is_cell`.
+pub fn check_rows(input: &[u8], is_cell: impl Fn(u8) -> bool) -> Result<(), Error> {
+    let mut width = None;
+    let mut offset = 0;
+    for line in input.split(|&c| c == b'\n') {
+        if let Some(column) = line.iter().position(|&c| !is_cell(c)) {
+            return Err(Error::parse_at(input, offset + column));
+        }
+        let width = *width.get_or_insert(line.len());
+        // A trailing newline ends the last row instead of starting an empty one.
+        let is_trailing_newline = line.is_empty() && offset == input.len();
+        if line.len() != width && !is_trailing_newline {
+            return Err(Error::parse_at(input, offset + line.len().min(width)));
+        }
+        offset += line.len() + 1;
+    }
+    match width {
+        Some(0) | None => Err(Error::Invalid("empty grid")),
+        Some(_) => Ok(()),
+    }
+}
+
+/// Reasons [`Grid::parse`] rejects an input. Rows and columns are 1-based.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum GridParseError {
+    UnknownCell {
+        row: usize,
+        column: usize,
+        cell: char,
+    },
+    RaggedRow {
+        row: usize,
+        length: usize,
+        expected: usize,
+    },
+    Empty,
+}
+
+impl Display for GridParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GridParseError::UnknownCell { row, column, cell } => {
+                write!(f, "unknown cell {:?} at {}:{}", cell, row, column)
+            }
+            GridParseError::RaggedRow {
+                row,
+                length,
+                expected,
+            } => write!(
+                f,
+                "row {} has {} cells instead of {}",
+                row, length, expected
+            ),
+            GridParseError::Empty => write!(f, "the grid has no cells"),
+        }
+    }
+}
+
+impl std::error::Error for GridParseError {}
+
+impl From<GridParseError> for Error {
+    fn from(value: GridParseError) -> Self {
+        match value {
+            GridParseError::UnknownCell { row, column, .. } => Error::Parse { line: row, column },
+            GridParseError::RaggedRow {
+                row,
+                length,
+                expected,
+            } => Error::Parse {
+                line: row,
+                column: length.min(expected) + 1,
+            },
+            GridParseError::Empty => Error::Invalid("empty grid"),
+        }
+    }
+}
+
+/// The reverse of [`CellDisplay`], for cells which are written as a single character.
+pub trait CellParse: Sized {
+    fn parse(c: char) -> Option<Self>;
+}
+
+impl CellParse for bool {
+    fn parse(c: char) -> Option<Self> {
+        match c {
+            '#' => Some(true),
+            '.' => Some(false),
+            _ => None,
+        }
+    }
+}
+
+/// Accepts what [`Direction::from_char`] does.
+impl CellParse for Direction {
+    fn parse(c: char) -> Option<Self> {
+        Direction::from_char(c)
+    }
+}
+
+impl<T: CellParse> Grid<T> {
+    /// Parses one row per line, rejecting unknown cells, ragged rows and grids without cells.
+    pub fn parse(input: &str) -> Result<Self, GridParseError> {
+        let mut data = vec![];
+        let mut width = None;
+        let mut height = 0;
+
+        for (y, line) in input.lines().enumerate() {
+            let start = data.len();
+            for (x, c) in line.chars().enumerate() {
+                data.push(T::parse(c).ok_or(GridParseError::UnknownCell {
+                    row: y + 1,
+                    column: x + 1,
+                    cell: c,
+                })?);
+            }
+            let length = data.len() - start;
+            let expected = *width.get_or_insert(length);
+            if length != expected {
+                return Err(GridParseError::RaggedRow {
+                    row: y + 1,
+                    length,
+                    expected,
+                });
+            }
+            height += 1;
+        }
+
+        match width {
+            None | Some(0) => Err(GridParseError::Empty),
+            Some(width) => Ok(Self {
+                dimensions: Dimensions::from_height_width(height, width),
+                data,
+            }),
+        }
+    }
+}
+
 pub trait CellDisplay {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result;
 }
@@ -181,20 +697,10 @@ where
     T: CellDisplay,
 {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        self.data
-            .chunks_exact(self.dimensions.1)
-            .try_for_each(|row| {
-                row.iter().try_for_each(|value| value.fmt(f))?;
-                f.write_char('\n')
-            })
-    }
-}
-
-impl Extend<(usize, usize)> for Grid<bool> {
-    fn extend<T: IntoIterator<Item = (usize, usize)>>(&mut self, iter: T) {
-        iter.into_iter().for_each(|position: (usize, usize)| {
-            self[position.0][position.1] = true;
-        });
+        self.rows().try_for_each(|row| {
+            row.into_iter().try_for_each(|value| value.fmt(f))?;
+            f.write_char('\n')
+        })
     }
 }
 
@@ -208,7 +714,7 @@ impl Extend<Position> for Grid<bool> {
 
 pub struct BackedGrid<'a, I> {
     data: &'a [I],
-    pub dimensions: (usize, usize),
+    pub dimensions: Dimensions,
     row_stride: usize,
 }
 
@@ -224,7 +730,7 @@ impl<'a, I> BackedGrid<'a, I> {
         let row_stride = width + 1;
         Self {
             data,
-            dimensions: (data.len().div_ceil(row_stride), width),
+            dimensions: Dimensions::from_height_width(data.len().div_ceil(row_stride), width),
             row_stride,
         }
     }
@@ -233,36 +739,335 @@ impl<'a, I> BackedGrid<'a, I> {
         pos.0 * self.row_stride + pos.1
     }
 
+    /// The cell at `pos` converted, for example from a byte of the input to a tile.
     #[inline]
-    pub fn get<T>(&self, pos: &Position) -> T
+    pub fn get_as<T>(&self, pos: &Position) -> T
     where
         &'a I: Into<T>,
     {
         (&self.data[self.index(pos)]).into()
     }
 
-    pub fn iter<T>(&'a self) -> impl Iterator<Item = (Position, T)> + '_
+    /// Every cell converted like [`BackedGrid::get_as`], row by row.
+    pub fn iter_as<T>(&'a self) -> impl Iterator<Item = (Position, T)> + 'a
     where
         &'a I: Into<T>,
     {
         self.data.iter().enumerate().filter_map(move |(i, value)| {
-            let pair = div_rem(i, self.row_stride);
-            if pair.1 >= self.dimensions.1 {
+            let pos = position_at(i, self.row_stride);
+            if pos.x() >= self.dimensions.width() {
                 None
             } else {
-                Some((pair.into(), value.into()))
+                Some((pos, value.into()))
             }
         })
     }
+}
 
-    pub fn positions(&self) -> impl Iterator<Item = Position> + '_ {
-        self.data.iter().enumerate().filter_map(move |(i, _)| {
-            let pair = div_rem(i, self.row_stride);
-            if pair.1 >= self.dimensions.1 {
-                None
+impl<I> GridView for BackedGrid<'_, I> {
+    type Cell = I;
+
+    fn dimensions(&self) -> Dimensions {
+        self.dimensions
+    }
+
+    #[inline]
+    fn get(&self, pos: &Position) -> &I {
+        &self.data[self.index(pos)]
+    }
+}
+
+/// A rectangular part of another grid, see [`GridView::view`].
+pub struct SubGrid<'g, G> {
+    grid: &'g G,
+    top_left: Position,
+    dimensions: Dimensions,
+}
+
+impl<G: GridView> GridView for SubGrid<'_, G> {
+    type Cell = G::Cell;
+
+    fn dimensions(&self) -> Dimensions {
+        self.dimensions
+    }
+
+    fn get(&self, pos: &Position) -> &G::Cell {
+        assert!(self.in_bounds(pos));
+        self.grid.get(&Position(
+            self.top_left.y() + pos.y(),
+            self.top_left.x() + pos.x(),
+        ))
+    }
+}
+
+/// One of the eight ways to rotate and mirror a grid: first an optional transpose, then optional
+/// flips of each axis.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Transform {
+    transpose: bool,
+    flip_y: bool,
+    flip_x: bool,
+}
+
+impl Transform {
+    pub const IDENTITY: Self = Self::new(false, false, false);
+    pub const ROTATE90: Self = Self::new(true, true, false);
+    pub const ROTATE180: Self = Self::new(false, true, true);
+    pub const ROTATE270: Self = Self::new(true, false, true);
+    pub const FLIP_HORIZONTAL: Self = Self::new(false, false, true);
+    pub const FLIP_VERTICAL: Self = Self::new(false, true, false);
+    pub const TRANSPOSE: Self = Self::new(true, false, false);
+
+    const fn new(transpose: bool, flip_y: bool, flip_x: bool) -> Self {
+        Self {
+            transpose,
+            flip_y,
+            flip_x,
+        }
+    }
+
+    /// Dimensions of the transformed grid.
+    pub fn dimensions(&self, dimensions: Dimensions) -> Dimensions {
+        match self.transpose {
+            true => dimensions.transposed(),
+            false => dimensions,
+        }
+    }
+
+    /// Position in a grid of `dimensions` that ends up at `pos` after the transform.
+    pub fn source(&self, dimensions: Dimensions, pos: &Position) -> Position {
+        let (y, x) = match self.transpose {
+            true => (pos.x(), pos.y()),
+            false => (pos.y(), pos.x()),
+        };
+        Position(
+            if self.flip_y {
+                dimensions.height() - 1 - y
             } else {
-                Some(pair.into())
-            }
-        })
+                y
+            },
+            if self.flip_x {
+                dimensions.width() - 1 - x
+            } else {
+                x
+            },
+        )
+    }
+}
+
+/// A rotated or mirrored view of another grid, see [`GridView::transform`].
+pub struct Transformed<'g, G> {
+    grid: &'g G,
+    transform: Transform,
+}
+
+impl<G: GridView> GridView for Transformed<'_, G> {
+    type Cell = G::Cell;
+
+    fn dimensions(&self) -> Dimensions {
+        self.transform.dimensions(self.grid.dimensions())
+    }
+
+    #[inline]
+    fn get(&self, pos: &Position) -> &G::Cell {
+        assert!(self.in_bounds(pos));
+        self.grid
+            .get(&self.transform.source(self.grid.dimensions(), pos))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn neighbours() {
+        let grid = Grid::from_rows([[1, 2, 3], [4, 5, 6]]);
+        let values = |pos| {
+            (
+                grid.neighbours4(&pos).map(|(_, v)| *v).collect::<Vec<_>>(),
+                grid.neighbours8(&pos).map(|(_, v)| *v).collect::<Vec<_>>(),
+            )
+        };
+        assert_eq!(values(Position(0, 0)), (vec![2, 4], vec![2, 5, 4]));
+        assert_eq!(values(Position(1, 1)), (vec![2, 6, 4], vec![2, 3, 6, 4, 1]));
+        assert_eq!(grid.get_checked(&Position(2, 0)), None);
+        assert_eq!(grid.get_offset(&Position(1, 2), -1, -2), Some(&1));
+        assert_eq!(grid.get_offset(&Position(1, 2), 0, 1), None);
+    }
+    #[test]
+    fn backed_neighbours() {
+        let grid = BackedGrid::from_data_and_row_separator(b"abc\ndef\n".as_slice(), b'\n');
+        assert_eq!(
+            grid.neighbours8(&Position(0, 2)).collect::<Vec<_>>(),
+            [
+                (Position(1, 2), &b'f'),
+                (Position(1, 1), &b'e'),
+                (Position(0, 1), &b'b')
+            ]
+        );
+        assert_eq!(grid.get_checked(&Position(0, 3)), None);
+        assert_eq!(grid.get_offset(&Position(0, 0), 1, 1), Some(&b'e'));
+    }
+    #[test]
+    fn views() {
+        let backed = BackedGrid::from_data_and_row_separator(b"abcd\nefgh\nijkl".as_slice(), b'\n');
+        let owned = backed.to_grid();
+        assert_eq!(owned.dimensions, Dimensions::from_height_width(3, 4));
+        assert!(owned.iter().eq(backed.iter()));
+
+        let view = backed.view(Position(1, 1), Dimensions::from_height_width(2, 3));
+        let rows = view
+            .rows()
+            .map(|row| row.copied().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(rows, [b"fgh", b"jkl"]);
+        let inner = view.view(Position(1, 0), Dimensions::from_height_width(1, 2));
+        assert_eq!(inner.get(&Position(0, 1)), &b'k');
+        assert_eq!(
+            inner.neighbours4(&Position(0, 0)).collect::<Vec<_>>(),
+            [(Position(0, 1), &b'k')]
+        );
+        assert_eq!(
+            owned
+                .view(Position(0, 0), Dimensions::from_height_width(0, 4))
+                .positions()
+                .count(),
+            0
+        );
+    }
+    #[test]
+    fn transforms() {
+        let grid = Grid::from_rows([[1, 2, 3], [4, 5, 6]]);
+        let rows = |grid: Grid<i32>| grid.row_slices().map(<[i32]>::to_vec).collect::<Vec<_>>();
+        assert_eq!(rows(grid.rotated90()), [[4, 1], [5, 2], [6, 3]]);
+        assert_eq!(rows(grid.rotated180()), [[6, 5, 4], [3, 2, 1]]);
+        assert_eq!(rows(grid.rotated270()), [[3, 6], [2, 5], [1, 4]]);
+        assert_eq!(rows(grid.flipped_horizontal()), [[3, 2, 1], [6, 5, 4]]);
+        assert_eq!(rows(grid.flipped_vertical()), [[4, 5, 6], [1, 2, 3]]);
+        assert_eq!(rows(grid.transposed()), [[1, 4], [2, 5], [3, 6]]);
+
+        assert_eq!(grid.rotate90().rotate90().to_grid(), grid.rotated180());
+        assert_eq!(grid.rotate90().rotate270().to_grid(), grid);
+        assert_eq!(grid.transform(Transform::IDENTITY).to_grid(), grid);
+        assert_eq!(grid.rotate90().get_offset(&Position(0, 0), 2, 1), Some(&3));
+    }
+    #[test]
+    fn columns() {
+        let mut grid = Grid::from_rows([[1, 2, 1], [4, 5, 4], [7, 8, 0]]);
+        assert!(grid.get_column(1).eq(&[2, 5, 8]));
+        assert!(grid.get_column(2).rev().eq(&[0, 4, 1]));
+        assert!((0..3).all(|i| grid.get_column(i).len() == 3));
+        assert!(!grid.columns_equal(0, 2));
+        assert_eq!(grid.column_mismatches(0, 2), 1);
+        assert_eq!(grid.column_mismatches(0, 1), 3);
+
+        grid.get_column_mut(2).for_each(|cell| *cell += 1);
+        let mut empty = Grid::from_dimensions(Dimensions::from_height_width(0, 3), 0);
+        assert_eq!(empty.get_column(2).len(), 0);
+        assert_eq!(empty.get_column_mut(2).len(), 0);
+        assert!(grid.column(2).eq(&[2, 5, 1]));
+        assert!(grid
+            .transposed()
+            .row_slices()
+            .eq(grid.columns().map(|c| c.copied().collect::<Vec<_>>())));
+    }
+    #[test]
+    fn parse() {
+        let grid = Grid::<bool>::parse("#.\n.#\n").unwrap();
+        assert_eq!(grid.dimensions, Dimensions::from_height_width(2, 2));
+        assert_eq!(grid.count(), 2);
+        assert_eq!(
+            Grid::<bool>::parse("#.\n.x"),
+            Err(GridParseError::UnknownCell {
+                row: 2,
+                column: 2,
+                cell: 'x'
+            })
+        );
+        assert_eq!(
+            Grid::<bool>::parse("#.\n#.#"),
+            Err(GridParseError::RaggedRow {
+                row: 2,
+                length: 3,
+                expected: 2
+            })
+        );
+        assert_eq!(Grid::<bool>::parse("\n"), Err(GridParseError::Empty));
+        assert_eq!(
+            Grid::<Direction>::parse("^>\nv<")
+                .unwrap()
+                .values()
+                .collect::<Vec<_>>(),
+            [
+                &Direction::Up,
+                &Direction::Right,
+                &Direction::Down,
+                &Direction::Left
+            ]
+        );
+        assert_eq!(
+            Grid::try_from_rows([vec![1], vec![]]),
+            Err(GridParseError::RaggedRow {
+                row: 2,
+                length: 0,
+                expected: 1
+            })
+        );
+        assert_eq!(
+            Grid::<u8>::from_rows([[]; 3]).dimensions,
+            Dimensions::from_height_width(3, 0)
+        );
+    }
+    #[test]
+    fn flood_fills() {
+        let grid = Grid::<bool>::parse("..#..\n.#.#.\n#...#\n.#.#.\n..#..").unwrap();
+        let is_open = |_: &Position, cell: &bool| !cell;
+        let centre = flood_fill(&grid, [Position(2, 2)], Connectivity::Four, is_open);
+        assert_eq!(centre.count(), 5);
+        assert!(!centre.touches_border());
+        let corners = flood_fill(&grid, [Position(0, 0)], Connectivity::Eight, is_open);
+        assert_eq!(corners.count(), 17);
+        assert!(corners.touches_border());
+        assert!(flood_fill(&grid, [Position(0, 2)], Connectivity::Four, is_open).count() == 0);
+
+        let four = label_components(&grid, Connectivity::Four, is_open);
+        assert_eq!(four.sizes, [8, 3, 3, 5, 3, 3]);
+        assert_eq!(four.labels[2][2], 3);
+        assert_eq!(
+            four.touching_border(),
+            [true, true, true, false, true, true]
+        );
+        let eight = label_components(&grid, Connectivity::Eight, is_open);
+        assert_eq!(eight.count(), 1);
+    }
+    #[test]
+    fn backed_components() {
+        let grid = BackedGrid::from_data_and_row_separator(b"aab\nabb\nccb\n".as_slice(), b'\n');
+        let components = label_components(&grid, Connectivity::Four, |_, &c| c != b'b');
+        assert_eq!(components.count(), 1);
+        assert_eq!(components.sizes, [4, 5]);
+        let b = flood_fill(&grid, [Position(0, 2)], Connectivity::Four, |_, &c| {
+            c == b'b'
+        });
+        assert_eq!(
+            b.iter().collect::<Vec<_>>(),
+            components
+                .labels
+                .iter()
+                .filter(|(_, &l)| l == 0)
+                .map(|(p, _)| p)
+                .collect::<Vec<_>>()
+        );
+    }
+    #[test]
+    fn from_points() {
+        let grid = Grid::from_points([Position(0, 2), Position(1, 0)].into_iter());
+        assert_eq!(grid.dimensions, Dimensions::from_height_width(2, 3));
+        assert_eq!(grid.to_string(), "  █\n█  \n");
+        assert_eq!(
+            Grid::from_points(std::iter::empty()).dimensions,
+            Dimensions::from_height_width(0, 0)
+        );
     }
 }
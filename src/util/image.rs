@@ -0,0 +1,137 @@
+//! Export of grids as binary PPM (colour) and PGM (grey) images, which most image viewers open.
+
+use crate::util::grid::GridView;
+use std::io::{self, Write};
+
+/// The colour of a cell when exported as an image, the counterpart of `CellDisplay`.
+pub trait CellColor {
+    fn color(&self) -> [u8; 3];
+
+    fn gray(&self) -> u8 {
+        let [r, g, b] = self.color();
+        ((r as u32 * 299 + g as u32 * 587 + b as u32 * 114) / 1000) as u8
+    }
+}
+
+impl CellColor for bool {
+    fn color(&self) -> [u8; 3] {
+        match self {
+            true => [255; 3],
+            false => [0; 3],
+        }
+    }
+}
+
+impl CellColor for [u8; 3] {
+    fn color(&self) -> [u8; 3] {
+        *self
+    }
+}
+
+/// Maps `t` from 0 to 1 onto black, red, yellow and white, for heat maps.
+pub fn heat(t: f64) -> [u8; 3] {
+    let t = t.clamp(0.0, 1.0) * 3.0;
+    let channel = |offset: f64| ((t - offset).clamp(0.0, 1.0) * 255.0).round() as u8;
+    [channel(0.0), channel(1.0), channel(2.0)]
+}
+
+fn write_pixels<G: GridView, const N: usize>(
+    grid: &G,
+    scale: usize,
+    header: &str,
+    out: &mut impl Write,
+    pixel: impl Fn(&G::Cell) -> [u8; N],
+) -> io::Result<()> {
+    assert!(scale > 0);
+    let (height, width) = (grid.dimensions().height(), grid.dimensions().width());
+    write!(
+        out,
+        "{}\n{} {}\n255\n",
+        header,
+        width * scale,
+        height * scale
+    )?;
+    let mut line = Vec::with_capacity(width * scale * N);
+    for row in grid.rows() {
+        line.clear();
+        for cell in row {
+            let pixel = pixel(cell);
+            for _ in 0..scale {
+                line.extend_from_slice(&pixel);
+            }
+        }
+        for _ in 0..scale {
+            out.write_all(&line)?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes a PPM image with `scale` by `scale` pixels per cell, coloured by `color`.
+pub fn write_ppm_with<G: GridView>(
+    grid: &G,
+    scale: usize,
+    out: &mut impl Write,
+    color: impl Fn(&G::Cell) -> [u8; 3],
+) -> io::Result<()> {
+    write_pixels(grid, scale, "P6", out, color)
+}
+
+pub fn write_ppm<G>(grid: &G, scale: usize, out: &mut impl Write) -> io::Result<()>
+where
+    G: GridView,
+    G::Cell: CellColor,
+{
+    write_ppm_with(grid, scale, out, CellColor::color)
+}
+
+/// Writes a PGM image with `scale` by `scale` pixels per cell, shaded by `gray`.
+pub fn write_pgm_with<G: GridView>(
+    grid: &G,
+    scale: usize,
+    out: &mut impl Write,
+    gray: impl Fn(&G::Cell) -> u8,
+) -> io::Result<()> {
+    write_pixels(grid, scale, "P5", out, |cell| [gray(cell)])
+}
+
+pub fn write_pgm<G>(grid: &G, scale: usize, out: &mut impl Write) -> io::Result<()>
+where
+    G: GridView,
+    G::Cell: CellColor,
+{
+    write_pgm_with(grid, scale, out, CellColor::gray)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::grid::Grid;
+
+    #[test]
+    fn ppm() {
+        let grid = Grid::from_rows([[true, false]]);
+        let mut out = vec![];
+        write_ppm(&grid, 2, &mut out).unwrap();
+        let mut expected = b"P6\n4 2\n255\n".to_vec();
+        for _ in 0..2 {
+            expected.extend([255; 6]);
+            expected.extend([0; 6]);
+        }
+        assert_eq!(out, expected);
+    }
+    #[test]
+    fn pgm() {
+        let grid = Grid::from_rows([[1u8, 9], [5, 0]]);
+        let mut out = vec![];
+        write_pgm_with(&grid.transpose(), 1, &mut out, |&value| value * 10).unwrap();
+        assert_eq!(out, b"P5\n2 2\n255\n\x0a\x32\x5a\x00");
+    }
+    #[test]
+    fn heat_map() {
+        assert_eq!(heat(0.0), [0, 0, 0]);
+        assert_eq!(heat(0.5), [255, 128, 0]);
+        assert_eq!(heat(1.0), [255, 255, 255]);
+        assert_eq!([true, false].map(|cell| cell.gray()), [255, 0]);
+    }
+}
@@ -1,4 +1,10 @@
+pub mod bitgrid;
 pub mod grid;
+pub mod image;
+pub mod ocr;
 pub mod parser;
 pub mod position;
+pub mod render;
+pub mod rng;
 pub mod solver;
+pub mod sparsegrid;
@@ -0,0 +1,261 @@
+use crate::util::grid::{Grid, GridView};
+use crate::util::position::Position;
+
+struct Font {
+    height: usize,
+    width: usize,
+    /// Horizontal distance between the start of two consecutive letters.
+    pitch: usize,
+    /// Whether the letters start every `pitch` columns from the left edge of the grid, as on the
+    /// screens drawn in the small font, instead of at the leftmost lit column.
+    aligned: bool,
+    glyphs: &'static [(char, &'static str)],
+}
+
+const SMALL: Font = Font {
+    height: 6,
+    width: 4,
+    pitch: 5,
+    aligned: true,
+    glyphs: &[
+        ('A', ".##.#..##..######..##..#"),
+        ('B', "###.#..####.#..##..####."),
+        ('C', ".##.#..##...#...#..#.##."),
+        ('E', "#####...###.#...#...####"),
+        ('F', "#####...###.#...#...#..."),
+        ('G', ".##.#..##...#.###..#.###"),
+        ('H', "#..##..######..##..##..#"),
+        ('I', ".###..#...#...#...#..###"),
+        ('J', "..##...#...#...##..#.##."),
+        ('K', "#..##.#.##..#.#.#.#.#..#"),
+        ('L', "#...#...#...#...#...####"),
+        ('O', ".##.#..##..##..##..#.##."),
+        ('P', "###.#..##..####.#...#..."),
+        ('R', "###.#..##..####.#.#.#..#"),
+        ('S', ".####...#....##....####."),
+        ('U', "#..##..##..##..##..#.##."),
+        ('Y', "#...##...#.#.#...#....#....#.."),
+        ('Z', "####...#..#..#..#...####"),
+    ],
+};
+
+const LARGE: Font = Font {
+    height: 10,
+    width: 6,
+    pitch: 8,
+    aligned: false,
+    glyphs: &[
+        (
+            'A',
+            "..##...#..#.#....##....##....########....##....##....##....#",
+        ),
+        (
+            'B',
+            "#####.#....##....##....######.#....##....##....##....######.",
+        ),
+        (
+            'C',
+            ".####.#....##.....#.....#.....#.....#.....#.....#....#.####.",
+        ),
+        (
+            'E',
+            "#######.....#.....#.....#####.#.....#.....#.....#.....######",
+        ),
+        (
+            'F',
+            "#######.....#.....#.....#####.#.....#.....#.....#.....#.....",
+        ),
+        (
+            'G',
+            ".####.#....##.....#.....#.....#..####....##....##...##.###.#",
+        ),
+        (
+            'H',
+            "#....##....##....##....########....##....##....##....##....#",
+        ),
+        (
+            'J',
+            "...###....#.....#.....#.....#.....#.....#.#...#.#...#..###..",
+        ),
+        (
+            'K',
+            "#....##...#.#..#..#.#...##....##....#.#...#..#..#...#.#....#",
+        ),
+        (
+            'L',
+            "#.....#.....#.....#.....#.....#.....#.....#.....#.....######",
+        ),
+        (
+            'N',
+            "#....###...###...##.#..##.#..##..#.##..#.##...###...###....#",
+        ),
+        (
+            'P',
+            "#####.#....##....##....######.#.....#.....#.....#.....#.....",
+        ),
+        (
+            'R',
+            "#####.#....##....##....######.#..#..#...#.#...#.#....##....#",
+        ),
+        (
+            'X',
+            "#....##....#.#..#..#..#...##....##...#..#..#..#.#....##....#",
+        ),
+        (
+            'Z',
+            "######.....#.....#....#....#....#....#....#.....#.....######",
+        ),
+    ],
+};
+
+impl Font {
+    fn matches(&self, grid: &Grid<bool>, top: usize, left: usize, glyph: &[u8]) -> bool {
+        // A few glyphs like 'Y' are wider and fill the gap before the next letter.
+        let width = glyph.len() / self.height;
+        (0..self.height).all(|j| {
+            (0..width).all(|i| {
+                // The last letter may be cut off by the edge of the grid if it ends in empty columns.
+                let lit =
+                    left + i < grid.dimensions.width() && *grid.get(&Position(top + j, left + i));
+                lit == (glyph[j * width + i] == b'#')
+            })
+        })
+    }
+
+    fn decode_from(
+        &self,
+        grid: &Grid<bool>,
+        top: usize,
+        left: usize,
+        right: usize,
+    ) -> Option<String> {
+        (left..right)
+            .step_by(self.pitch)
+            .map(|x| {
+                self.glyphs
+                    .iter()
+                    .find(|(_, glyph)| self.matches(grid, top, x, glyph.as_bytes()))
+                    .map(|(letter, _)| *letter)
+            })
+            .collect()
+    }
+}
+
+/// Reads the letters rendered in a grid using the 4×6 or 6×10 Advent of Code alphabet.
+///
+/// The lit cells are cropped to their bounding box first, so surrounding margins are allowed, and the
+/// font is chosen from the height of that box. Small letters stay on their columns from the left
+/// edge though, so one with an empty first column like 'I' does not shift the letters after it.
+/// Returns `None` when any glyph is not recognised.
+pub fn recognise(grid: &Grid<bool>) -> Option<String> {
+    let (top, left, bottom, right) = grid.iter().filter(|(_, lit)| **lit).fold(
+        (usize::MAX, usize::MAX, 0, 0),
+        |(top, left, bottom, right), (pos, _)| {
+            (
+                top.min(pos.y()),
+                left.min(pos.x()),
+                bottom.max(pos.y() + 1),
+                right.max(pos.x() + 1),
+            )
+        },
+    );
+    if top > bottom {
+        return None;
+    }
+
+    let font = [SMALL, LARGE]
+        .into_iter()
+        .find(|font| font.height == bottom - top)?;
+
+    let left = match font.aligned {
+        true => left - left % font.pitch,
+        false => left,
+    };
+    font.decode_from(grid, top, left, right)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid_from_str(input: &str) -> Grid<bool> {
+        Grid::from_rows(input.lines().map(|line| line.chars().map(|c| c == '#')))
+    }
+
+    #[test]
+    fn glyph_sizes() {
+        for font in [SMALL, LARGE] {
+            for (letter, glyph) in font.glyphs {
+                assert!(
+                    [font.width, font.pitch].contains(&(glyph.len() / font.height))
+                        && glyph.len() % font.height == 0,
+                    "{}",
+                    letter
+                );
+            }
+        }
+    }
+    #[test]
+    fn small_font() {
+        let grid = grid_from_str(
+            "\
+###..#....####.####.#..#.####.###..#....
+#..#.#....#.......#.#.#..#....#..#.#....
+#..#.#....###....#..##...###..#..#.#....
+###..#....#.....#...#.#..#....###..#....
+#....#....#....#....#.#..#....#....#....
+#....####.####.####.#..#.####.#....####.",
+        );
+        assert_eq!(recognise(&grid), Some("PLEZKEPL".to_string()));
+    }
+    #[test]
+    fn small_font_with_margin() {
+        let grid = grid_from_str(
+            "\
+............
+.......##...
+........#...
+........#...
+........#...
+.....#..#...
+......##....
+............",
+        );
+        assert_eq!(recognise(&grid), Some("J".to_string()));
+    }
+    #[test]
+    fn small_font_narrow_and_wide_letters() {
+        let grid = grid_from_str(
+            "\
+.###...##.#...#
+..#.....#.#...#
+..#.....#..#.#.
+..#.....#...#..
+..#..#..#...#..
+.###..##....#..",
+        );
+        assert_eq!(recognise(&grid), Some("IJY".to_string()));
+    }
+    #[test]
+    fn large_font() {
+        let grid = grid_from_str(
+            "\
+..#....#..######..#####.
+..#....#..#.......#....#
+...#..#...#.......#....#
+...#..#...#.......#....#
+....##....#####...#####.
+....##....#.......#..#..
+...#..#...#.......#...#.
+...#..#...#.......#...#.
+..#....#..#.......#....#
+..#....#..#.......#....#",
+        );
+        assert_eq!(recognise(&grid), Some("XFR".to_string()));
+    }
+    #[test]
+    fn unknown_glyph() {
+        let grid = grid_from_str("#.#.\n.#.#\n#.#.\n.#.#\n#.#.\n.#.#");
+        assert_eq!(recognise(&grid), None);
+    }
+}
@@ -1,3 +1,19 @@
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+/// Offsets `(dy, dx)` of the orthogonal neighbours, clockwise from up.
+pub const NEIGHBOURS4: [(isize, isize); 4] = [(-1, 0), (0, 1), (1, 0), (0, -1)];
+/// Offsets `(dy, dx)` of the orthogonal and diagonal neighbours, clockwise from up.
+pub const NEIGHBOURS8: [(isize, isize); 8] = [
+    (-1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+    (1, 0),
+    (1, -1),
+    (0, -1),
+    (-1, -1),
+];
+
 #[derive(Clone, Copy, PartialEq, Debug)]
 pub enum RotationalDirection {
     Clockwise,
@@ -25,41 +41,134 @@ pub enum Direction {
 }
 
 impl Direction {
+    /// Clockwise from up, in the order of the discriminants.
+    pub const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Right,
+        Direction::Down,
+        Direction::Left,
+    ];
+
     #[must_use]
     pub fn with_rotation(self, rotational_direction: &RotationalDirection) -> Self {
-        match (self, rotational_direction) {
-            (Direction::Up, RotationalDirection::Clockwise) => Direction::Right,
-            (Direction::Up, RotationalDirection::Anticlockwise) => Direction::Left,
-            (Direction::Down, RotationalDirection::Clockwise) => Direction::Left,
-            (Direction::Down, RotationalDirection::Anticlockwise) => Direction::Right,
-            (Direction::Right, RotationalDirection::Clockwise) => Direction::Down,
-            (Direction::Right, RotationalDirection::Anticlockwise) => Direction::Up,
-            (Direction::Left, RotationalDirection::Clockwise) => Direction::Up,
-            (Direction::Left, RotationalDirection::Anticlockwise) => Direction::Down,
+        let quarter_turns = match rotational_direction {
+            RotationalDirection::Clockwise => 1,
+            RotationalDirection::Anticlockwise => 3,
+        };
+        Self::ALL[(self as usize + quarter_turns) % 4]
+    }
+
+    #[must_use]
+    pub fn opposite(self) -> Self {
+        Self::ALL[(self as usize + 2) % 4]
+    }
+
+    /// The `(dy, dx)` of a step in this direction.
+    pub fn offset(self) -> (isize, isize) {
+        NEIGHBOURS4[self as usize]
+    }
+
+    /// Accepts the letters `U`, `R`, `D` and `L`, the compass points `N`, `E`, `S` and `W` and the
+    /// arrows `^`, `>`, `v` and `<`.
+    pub fn from_char(c: char) -> Option<Self> {
+        match c {
+            'U' | 'N' | '^' => Some(Direction::Up),
+            'R' | 'E' | '>' => Some(Direction::Right),
+            'D' | 'S' | 'v' => Some(Direction::Down),
+            'L' | 'W' | '<' => Some(Direction::Left),
+            _ => None,
         }
     }
 }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
-pub struct Dimensions(usize, usize);
+/// The orthogonal and diagonal directions, as a king moves in chess.
+#[derive(Clone, Copy, PartialEq, Debug, Eq, Hash)]
+pub enum Direction8 {
+    Up = 0,
+    UpRight = 1,
+    Right = 2,
+    DownRight = 3,
+    Down = 4,
+    DownLeft = 5,
+    Left = 6,
+    UpLeft = 7,
+}
+
+impl Direction8 {
+    /// Clockwise from up, in the order of the discriminants.
+    pub const ALL: [Direction8; 8] = [
+        Direction8::Up,
+        Direction8::UpRight,
+        Direction8::Right,
+        Direction8::DownRight,
+        Direction8::Down,
+        Direction8::DownLeft,
+        Direction8::Left,
+        Direction8::UpLeft,
+    ];
+
+    #[must_use]
+    pub fn opposite(self) -> Self {
+        Self::ALL[(self as usize + 4) % 8]
+    }
+
+    pub fn is_diagonal(self) -> bool {
+        self as usize % 2 == 1
+    }
+
+    /// The `(dy, dx)` of a step in this direction.
+    pub fn offset(self) -> (isize, isize) {
+        NEIGHBOURS8[self as usize]
+    }
+}
+
+impl From<Direction> for Direction8 {
+    fn from(value: Direction) -> Self {
+        Self::ALL[value as usize * 2]
+    }
+}
+
+/// The size of a grid. There is no conversion from a tuple, so it can not be mixed up with a
+/// position, and the constructor names the order of its arguments.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct Dimensions {
+    height: usize,
+    width: usize,
+}
+
 impl Dimensions {
+    pub const fn from_height_width(height: usize, width: usize) -> Self {
+        Self { height, width }
+    }
+
     pub fn height(&self) -> usize {
-        self.0
+        self.height
     }
     pub fn width(&self) -> usize {
-        self.1
+        self.width
     }
-}
 
-impl From<Dimensions> for (usize, usize) {
-    fn from(value: Dimensions) -> Self {
-        (value.height(), value.width())
+    pub fn area(&self) -> usize {
+        self.height * self.width
     }
-}
 
-impl From<(usize, usize)> for Dimensions {
-    fn from(value: (usize, usize)) -> Self {
-        Dimensions(value.0, value.1)
+    pub fn contains(&self, pos: &Position) -> bool {
+        pos.y() < self.height && pos.x() < self.width
+    }
+
+    /// The dimensions with height and width swapped, e.g. after a transpose.
+    #[must_use]
+    pub fn transposed(&self) -> Self {
+        Self {
+            height: self.width,
+            width: self.height,
+        }
+    }
+
+    /// All positions inside, row by row.
+    pub fn positions(&self) -> impl DoubleEndedIterator<Item = Position> {
+        let Dimensions { height, width } = *self;
+        (0..height).flat_map(move |y| (0..width).map(move |x| Position(y, x)))
     }
 }
 
@@ -86,13 +195,13 @@ impl Position {
                 Some(Self(self.0 - 1, self.1))
             }
             Direction::Right => {
-                if self.1 == dimensions.1 - 1 {
+                if self.1 == dimensions.width() - 1 {
                     return None;
                 }
                 Some(Self(self.0, self.1 + 1))
             }
             Direction::Down => {
-                if self.0 == dimensions.0 - 1 {
+                if self.0 == dimensions.height() - 1 {
                     return None;
                 }
                 Some(Self(self.0 + 1, self.1))
@@ -106,19 +215,289 @@ impl Position {
         }
     }
 
+    /// Moves by a signed offset, or `None` if that leaves the dimensions.
+    pub fn checked_offset(&self, dimensions: &Dimensions, dy: isize, dx: isize) -> Option<Self> {
+        let y = self.0.checked_add_signed(dy)?;
+        let x = self.1.checked_add_signed(dx)?;
+        let pos = Self(y, x);
+        dimensions.contains(&pos).then_some(pos)
+    }
+
     pub fn manhattan_distance(&self, other: &Position) -> usize {
         other.0.abs_diff(self.0) + other.1.abs_diff(self.1)
     }
 }
 
-impl From<Position> for (usize, usize) {
+/// A signed offset or point, for walks which are not confined to a grid.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct IVec2 {
+    pub y: i64,
+    pub x: i64,
+}
+
+impl IVec2 {
+    pub const ZERO: Self = Self::new(0, 0);
+
+    pub const fn new(y: i64, x: i64) -> Self {
+        Self { y, x }
+    }
+
+    pub fn manhattan_distance(&self, other: &Self) -> u64 {
+        self.y.abs_diff(other.y) + self.x.abs_diff(other.x)
+    }
+
+    /// The distance when diagonal steps are allowed as well.
+    pub fn chebyshev_distance(&self, other: &Self) -> u64 {
+        self.y.abs_diff(other.y).max(self.x.abs_diff(other.x))
+    }
+
+    /// The position at this point, or `None` if it lies outside the dimensions.
+    pub fn to_position(self, dimensions: &Dimensions) -> Option<Position> {
+        let y = usize::try_from(self.y).ok()?;
+        let x = usize::try_from(self.x).ok()?;
+        let pos = Position(y, x);
+        dimensions.contains(&pos).then_some(pos)
+    }
+}
+
+impl From<Direction> for IVec2 {
+    fn from(value: Direction) -> Self {
+        let (dy, dx) = value.offset();
+        Self::new(dy as i64, dx as i64)
+    }
+}
+
+impl From<Direction8> for IVec2 {
+    fn from(value: Direction8) -> Self {
+        let (dy, dx) = value.offset();
+        Self::new(dy as i64, dx as i64)
+    }
+}
+
+impl From<Position> for IVec2 {
     fn from(value: Position) -> Self {
-        (value.y(), value.x())
+        Self::new(value.y() as i64, value.x() as i64)
+    }
+}
+
+impl Add for IVec2 {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self::new(self.y + rhs.y, self.x + rhs.x)
     }
 }
 
-impl From<(usize, usize)> for Position {
-    fn from(value: (usize, usize)) -> Self {
-        Position(value.0, value.1)
+impl AddAssign for IVec2 {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for IVec2 {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self::new(self.y - rhs.y, self.x - rhs.x)
+    }
+}
+
+impl SubAssign for IVec2 {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl Neg for IVec2 {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::new(-self.y, -self.x)
+    }
+}
+
+impl Mul<i64> for IVec2 {
+    type Output = Self;
+
+    fn mul(self, rhs: i64) -> Self {
+        Self::new(self.y * rhs, self.x * rhs)
+    }
+}
+
+/// The neighbours of a cell on a hex grid whose columns are offset, so that it has neighbours to
+/// the north and south but not to the east and west.
+#[derive(Clone, Copy, PartialEq, Debug, Eq, Hash)]
+pub enum HexDirection {
+    North = 0,
+    NorthEast = 1,
+    SouthEast = 2,
+    South = 3,
+    SouthWest = 4,
+    NorthWest = 5,
+}
+
+impl HexDirection {
+    /// Clockwise from north, in the order of the discriminants.
+    pub const ALL: [HexDirection; 6] = [
+        HexDirection::North,
+        HexDirection::NorthEast,
+        HexDirection::SouthEast,
+        HexDirection::South,
+        HexDirection::SouthWest,
+        HexDirection::NorthWest,
+    ];
+
+    /// Parses the lowercase abbreviations `n`, `ne`, `se`, `s`, `sw` and `nw`.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "n" => Some(HexDirection::North),
+            "ne" => Some(HexDirection::NorthEast),
+            "se" => Some(HexDirection::SouthEast),
+            "s" => Some(HexDirection::South),
+            "sw" => Some(HexDirection::SouthWest),
+            "nw" => Some(HexDirection::NorthWest),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn opposite(self) -> Self {
+        Self::ALL[(self as usize + 3) % 6]
+    }
+}
+
+/// A cell on a hex grid in axial coordinates: `q` counts columns to the east, `r` counts steps to
+/// the south within a column.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct Hex {
+    pub q: i64,
+    pub r: i64,
+}
+
+impl Hex {
+    pub const fn new(q: i64, r: i64) -> Self {
+        Self { q, r }
+    }
+
+    #[must_use]
+    pub fn neighbour(self, direction: HexDirection) -> Self {
+        let (dq, dr) = match direction {
+            HexDirection::North => (0, -1),
+            HexDirection::NorthEast => (1, -1),
+            HexDirection::SouthEast => (1, 0),
+            HexDirection::South => (0, 1),
+            HexDirection::SouthWest => (-1, 1),
+            HexDirection::NorthWest => (-1, 0),
+        };
+        Self::new(self.q + dq, self.r + dr)
+    }
+
+    /// The number of steps between two cells.
+    pub fn distance(&self, other: &Self) -> u64 {
+        let (dq, dr) = (self.q - other.q, self.r - other.r);
+        (dq.unsigned_abs() + dr.unsigned_abs() + (dq + dr).unsigned_abs()) / 2
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vector_arithmetic() {
+        let start = IVec2::new(2, 3);
+        let mut walked = start;
+        walked += IVec2::from(Direction::Up) * 5;
+        walked -= IVec2::from(Direction::Left);
+        assert_eq!(walked, IVec2::new(-3, 4));
+        assert_eq!(walked - start, IVec2::new(-5, 1));
+        assert_eq!(-IVec2::from(Direction::Down), IVec2::from(Direction::Up));
+        assert_eq!(start.manhattan_distance(&walked), 6);
+        assert_eq!(start.chebyshev_distance(&walked), 5);
+    }
+    #[test]
+    fn position_conversions() {
+        let dimensions = Dimensions::from_height_width(3, 4);
+        let position = Position(2, 3);
+        let point = IVec2::from(position);
+        assert_eq!(point.to_position(&dimensions), Some(position));
+        for direction in [
+            Direction::Up,
+            Direction::Right,
+            Direction::Down,
+            Direction::Left,
+        ] {
+            assert_eq!(
+                (point + direction.into()).to_position(&dimensions),
+                position.checked_moved(&dimensions, &direction)
+            );
+        }
+        assert_eq!(IVec2::new(-1, 0).to_position(&dimensions), None);
+    }
+    #[test]
+    fn dimensions() {
+        let dimensions = Dimensions::from_height_width(2, 3);
+        assert_eq!(dimensions.area(), 6);
+        let positions = dimensions.positions().collect::<Vec<_>>();
+        assert_eq!(positions.len(), dimensions.area());
+        assert_eq!(positions[4], Position(1, 1));
+        assert!(positions.iter().all(|pos| dimensions.contains(pos)));
+        assert!(!dimensions.contains(&Position(0, 3)));
+        assert!(dimensions.transposed().contains(&Position(2, 1)));
+    }
+    #[test]
+    fn directions() {
+        for direction in Direction::ALL {
+            assert_eq!(direction.opposite().opposite(), direction);
+            assert_eq!(IVec2::from(direction.opposite()), -IVec2::from(direction));
+            let clockwise = direction.with_rotation(&RotationalDirection::Clockwise);
+            assert_eq!(
+                clockwise.with_rotation(&RotationalDirection::Anticlockwise),
+                direction
+            );
+            assert_eq!(
+                RotationalDirection::from_incoming_and_outgoing(&direction, &clockwise),
+                Some(RotationalDirection::Clockwise)
+            );
+            assert_eq!(IVec2::from(Direction8::from(direction)), direction.into());
+        }
+        let parsed = "URDL NESW ^>v<".split(' ').map(|group| {
+            group
+                .chars()
+                .map(|c| Direction::from_char(c).unwrap())
+                .collect::<Vec<_>>()
+        });
+        for group in parsed {
+            assert_eq!(group, Direction::ALL);
+        }
+        assert_eq!(Direction::from_char('x'), None);
+    }
+    #[test]
+    fn king_moves() {
+        let diagonals = Direction8::ALL.into_iter().filter(|d| d.is_diagonal());
+        assert_eq!(diagonals.count(), 4);
+        for direction in Direction8::ALL {
+            let step = IVec2::from(direction);
+            assert_eq!(IVec2::from(direction.opposite()), -step);
+            assert_eq!(step.chebyshev_distance(&IVec2::ZERO), 1);
+        }
+    }
+    #[test]
+    fn hex() {
+        let origin = Hex::default();
+        for direction in HexDirection::ALL {
+            let neighbour = origin.neighbour(direction);
+            assert_eq!(origin.distance(&neighbour), 1);
+            assert_eq!(neighbour.neighbour(direction.opposite()), origin);
+        }
+        let walk = |path: &str| {
+            path.split(',')
+                .map(|step| HexDirection::parse(step).unwrap())
+                .fold(origin, Hex::neighbour)
+        };
+        assert_eq!(origin.distance(&walk("ne,ne,ne")), 3);
+        assert_eq!(origin.distance(&walk("ne,ne,sw,sw")), 0);
+        assert_eq!(origin.distance(&walk("ne,ne,s,s")), 2);
+        assert_eq!(origin.distance(&walk("se,sw,se,sw,sw")), 3);
     }
 }
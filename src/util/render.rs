@@ -0,0 +1,113 @@
+//! Terminal output of a grid with coloured overlays, e.g. a path or the cells a solution counted.
+
+use crate::util::grid::{Grid, GridView};
+use crate::util::position::Position;
+use std::fmt::{Display, Formatter, Write};
+use std::io::IsTerminal;
+
+/// The foreground colours of the basic ANSI palette, with their SGR codes as discriminants.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Color {
+    Red = 31,
+    Green = 32,
+    Yellow = 33,
+    Blue = 34,
+    Magenta = 35,
+    Cyan = 36,
+}
+
+/// Draws a grid one character per cell, with overlays colouring (and optionally replacing) cells.
+///
+/// Overlays added later are drawn on top of earlier ones. Colours are only written when stdout is
+/// a terminal, unless overridden with [`Renderer::with_color`], so piped output stays plain text.
+pub struct Renderer {
+    glyphs: Grid<char>,
+    colors: Grid<Option<Color>>,
+    color: bool,
+}
+
+impl Renderer {
+    pub fn new<G: GridView>(base: &G, glyph: impl Fn(&G::Cell) -> char) -> Self {
+        Self {
+            glyphs: Grid::from_rows(base.rows().map(|row| row.map(&glyph))),
+            colors: Grid::from_dimensions(base.dimensions(), None),
+            color: std::io::stdout().is_terminal(),
+        }
+    }
+
+    pub fn with_color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Colours the cells at `positions`, keeping their characters.
+    pub fn overlay(mut self, color: Color, positions: impl IntoIterator<Item = Position>) -> Self {
+        for pos in positions {
+            *self.colors.get_mut(&pos) = Some(color);
+        }
+        self
+    }
+
+    /// Colours the cells at `positions` and draws them as `glyph`.
+    pub fn overlay_glyph(
+        mut self,
+        color: Color,
+        glyph: char,
+        positions: impl IntoIterator<Item = Position>,
+    ) -> Self {
+        for pos in positions {
+            *self.colors.get_mut(&pos) = Some(color);
+            *self.glyphs.get_mut(&pos) = glyph;
+        }
+        self
+    }
+}
+
+impl Display for Renderer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (glyphs, colors) in self.glyphs.row_slices().zip(self.colors.row_slices()) {
+            // Escape codes are only written where the colour changes, and reset at every line end.
+            let mut current = None;
+            for (&glyph, &color) in glyphs.iter().zip(colors) {
+                if self.color && color != current {
+                    match color {
+                        Some(color) => write!(f, "\x1b[{}m", color as u8)?,
+                        None => f.write_str("\x1b[0m")?,
+                    }
+                    current = color;
+                }
+                f.write_char(glyph)?;
+            }
+            if current.is_some() {
+                f.write_str("\x1b[0m")?;
+            }
+            f.write_char('\n')?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overlays() {
+        let grid = Grid::from_rows([[1u8, 2, 3], [4, 5, 6]]);
+        let renderer = Renderer::new(&grid, |&digit| (b'0' + digit) as char)
+            .overlay(Color::Red, [Position(0, 0), Position(0, 1)])
+            .overlay_glyph(Color::Green, '#', [Position(0, 1), Position(1, 2)]);
+        assert_eq!(
+            renderer.with_color(true).to_string(),
+            "\x1b[31m1\x1b[32m#\x1b[0m3\n45\x1b[32m#\x1b[0m\n"
+        );
+    }
+    #[test]
+    fn plain() {
+        let grid = Grid::from_rows([[true, false], [false, true]]);
+        let renderer = Renderer::new(&grid.transpose(), |&cell| if cell { '#' } else { '.' })
+            .overlay(Color::Blue, [Position(0, 1)])
+            .with_color(false);
+        assert_eq!(renderer.to_string(), "#.\n.#\n");
+    }
+}
@@ -1,5 +1,9 @@
-use rustc_hash::FxHashSet;
+use num::Zero;
+use rustc_hash::FxHashMap;
+use std::cmp::Reverse;
+use std::collections::hash_map::Entry;
 use std::collections::BinaryHeap;
+use std::hash::Hash;
 
 pub fn solve_fn<F, S, II, I>(mut next: F, mut states: Vec<S>)
 where
@@ -56,29 +60,120 @@ pub enum NodeResult {
     Next,
 }
 
-pub fn solve_breadth<F, S>(mut next: F, mut states: Vec<S>) -> (S, usize)
+/// Breadth-first search where every step costs one. `visit` sees every state once, in the order
+/// of its distance from the `starts`, and can stop the search at it. States further than
+/// `max_distance` are not visited. Returns the stopping state with its distance, and the distance
+/// of every state reached. After a stop, that includes part of the layer after the stopping state.
+fn breadth_first<S, K, I>(
+    starts: impl IntoIterator<Item = S>,
+    mut successors: impl FnMut(&S) -> I,
+    key: impl Fn(&S) -> K,
+    max_distance: usize,
+    mut visit: impl FnMut(&S, usize) -> NodeResult,
+) -> (Option<(S, usize)>, FxHashMap<K, usize>)
 where
-    F: FnMut(&S, usize, &mut FxHashSet<S>) -> NodeResult,
-    S: std::hash::Hash + Eq,
+    K: Hash + Eq,
+    I: IntoIterator<Item = S>,
 {
-    let mut round = 0;
-    loop {
-        let mut next_states = FxHashSet::with_capacity_and_hasher(states.len(), Default::default());
-        for state in states {
-            match next(&state, round, &mut next_states) {
-                NodeResult::Stop => return (state, round),
-                NodeResult::Next => {}
-            };
+    let mut distances = FxHashMap::default();
+    let mut layer = vec![];
+    for start in starts {
+        if let Entry::Vacant(entry) = distances.entry(key(&start)) {
+            entry.insert(0);
+            layer.push(start);
+        }
+    }
+
+    // The layers are swapped instead of allocated anew each round.
+    let mut next_layer = vec![];
+    for distance in 0..=max_distance {
+        if layer.is_empty() {
+            break;
         }
-        if next_states.is_empty() {
-            panic!("Result not found")
+        for state in layer.drain(..) {
+            if let NodeResult::Stop = visit(&state, distance) {
+                return (Some((state, distance)), distances);
+            }
+            if distance == max_distance {
+                continue;
+            }
+            for next in successors(&state) {
+                if let Entry::Vacant(entry) = distances.entry(key(&next)) {
+                    entry.insert(distance + 1);
+                    next_layer.push(next);
+                }
+            }
         }
-        states = next_states.into_iter().collect();
-        round += 1;
+        std::mem::swap(&mut layer, &mut next_layer);
     }
+    (None, distances)
+}
+
+/// The first state in breadth-first order for which `is_target` holds, with its distance from
+/// the `starts`. `None` if every reachable state was visited without finding one.
+pub fn bfs<S, K, I>(
+    starts: impl IntoIterator<Item = S>,
+    successors: impl FnMut(&S) -> I,
+    key: impl Fn(&S) -> K,
+    mut is_target: impl FnMut(&S) -> bool,
+) -> Option<(S, usize)>
+where
+    K: Hash + Eq,
+    I: IntoIterator<Item = S>,
+{
+    let visit = |state: &S, _| match is_target(state) {
+        true => NodeResult::Stop,
+        false => NodeResult::Next,
+    };
+    breadth_first(starts, successors, key, usize::MAX, visit).0
+}
+
+/// The distance from the `starts` to every reachable state, by key.
+pub fn bfs_distances<S, K, I>(
+    starts: impl IntoIterator<Item = S>,
+    successors: impl FnMut(&S) -> I,
+    key: impl Fn(&S) -> K,
+) -> FxHashMap<K, usize>
+where
+    K: Hash + Eq,
+    I: IntoIterator<Item = S>,
+{
+    breadth_first(starts, successors, key, usize::MAX, |_, _| NodeResult::Next).1
+}
+
+/// Every state at most `max_distance` steps from the `starts` with its distance, nearest first.
+///
+/// On a grid, the cells reachable in exactly `n` steps are those with a distance of the same
+/// parity as `n`, since any of them can be reached later by stepping back and forth.
+pub fn bfs_within<S, K, I>(
+    starts: impl IntoIterator<Item = S>,
+    successors: impl FnMut(&S) -> I,
+    key: impl Fn(&S) -> K,
+    max_distance: usize,
+) -> Vec<(S, usize)>
+where
+    S: Clone,
+    K: Hash + Eq,
+    I: IntoIterator<Item = S>,
+{
+    let mut reached = vec![];
+    breadth_first(starts, successors, key, max_distance, |state, distance| {
+        reached.push((state.clone(), distance));
+        NodeResult::Next
+    });
+    reached
 }
 
-pub fn solve_fn_priority<F, S>(mut next: F, states: Vec<S>) -> S
+pub fn solve_fn_priority<F, S>(next: F, states: Vec<S>) -> S
+where
+    S: Ord,
+    F: FnMut(&mut BinaryHeap<S>, &S) -> NodeResult,
+{
+    try_solve_fn_priority(next, states).expect("Stop condition not triggered")
+}
+
+/// Like [`solve_fn_priority`], but returns `None` if the states run out before stopping.
+pub fn try_solve_fn_priority<F, S>(mut next: F, states: Vec<S>) -> Option<S>
 where
     S: Ord,
     F: FnMut(&mut BinaryHeap<S>, &S) -> NodeResult,
@@ -88,8 +183,398 @@ where
     while let Some(current) = stack.pop() {
         match next(&mut stack, &current) {
             NodeResult::Next => {}
-            NodeResult::Stop => return current,
+            NodeResult::Stop => return Some(current),
         }
     }
-    panic!("Stop condition not triggered");
+    None
+}
+
+/// The cheapest path from any of the `starts` to a state for which `is_target` holds, as its cost
+/// and every state on it including both ends. `None` if no target can be reached.
+///
+/// States with the same `key` are treated as the same node, so the key should drop everything
+/// that does not influence where the path can go from there, e.g. the cost so far.
+pub fn dijkstra<S, K, C, I>(
+    starts: impl IntoIterator<Item = S>,
+    successors: impl FnMut(&S) -> I,
+    is_target: impl FnMut(&S) -> bool,
+    key: impl Fn(&S) -> K,
+) -> Option<(C, Vec<S>)>
+where
+    K: Hash + Eq,
+    C: Zero + Ord + Copy,
+    I: IntoIterator<Item = (S, C)>,
+{
+    a_star(starts, successors, is_target, key, |_| C::zero())
+}
+
+/// Like [`dijkstra`], but visits states in the order of their cost plus `heuristic`, which must
+/// never overestimate the remaining cost and must not drop by more than the cost of a step.
+pub fn a_star<S, K, C, I>(
+    starts: impl IntoIterator<Item = S>,
+    mut successors: impl FnMut(&S) -> I,
+    mut is_target: impl FnMut(&S) -> bool,
+    key: impl Fn(&S) -> K,
+    heuristic: impl Fn(&S) -> C,
+) -> Option<(C, Vec<S>)>
+where
+    K: Hash + Eq,
+    C: Zero + Ord + Copy,
+    I: IntoIterator<Item = (S, C)>,
+{
+    const NO_PARENT: usize = usize::MAX;
+
+    // Every state pushed so far with the index of the state it was reached from.
+    let mut nodes: Vec<(S, usize)> = vec![];
+    let mut costs = FxHashMap::default();
+    // Ordered by the estimate, ties go to the state which got further as it is more likely on the
+    // path.
+    let mut queue = BinaryHeap::new();
+
+    for start in starts {
+        if let Entry::Vacant(entry) = costs.entry(key(&start)) {
+            entry.insert(C::zero());
+            queue.push(Reverse((
+                heuristic(&start),
+                Reverse(C::zero()),
+                nodes.len(),
+            )));
+            nodes.push((start, NO_PARENT));
+        }
+    }
+
+    while let Some(Reverse((_, Reverse(cost), index))) = queue.pop() {
+        let state = &nodes[index].0;
+        // A cheaper way to the same node was found after this one was queued.
+        if costs[&key(state)] < cost {
+            continue;
+        }
+        if is_target(state) {
+            let mut indices = vec![index];
+            while nodes[*indices.last().unwrap()].1 != NO_PARENT {
+                indices.push(nodes[*indices.last().unwrap()].1);
+            }
+            let mut states = nodes
+                .into_iter()
+                .map(|(state, _)| Some(state))
+                .collect::<Vec<_>>();
+            let path = indices.iter().rev().map(|&i| states[i].take().unwrap());
+            return Some((cost, path.collect()));
+        }
+        for (next, step_cost) in successors(state) {
+            let next_cost = cost + step_cost;
+            match costs.entry(key(&next)) {
+                Entry::Occupied(entry) if *entry.get() <= next_cost => continue,
+                Entry::Occupied(mut entry) => {
+                    entry.insert(next_cost);
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(next_cost);
+                }
+            }
+            queue.push(Reverse((
+                next_cost + heuristic(&next),
+                Reverse(next_cost),
+                nodes.len(),
+            )));
+            nodes.push((next, index));
+        }
+    }
+    None
+}
+
+/// A priority queue for small non-negative integer priorities that never drop below the priority
+/// of the last state popped, as in Dijkstra's algorithm with integer costs (Dial's algorithm).
+/// Every priority gets a bucket, so pushing and popping are constant time.
+pub struct BucketQueue<S> {
+    buckets: Vec<Vec<S>>,
+    current: usize,
+}
+
+impl<S> BucketQueue<S> {
+    pub fn push(&mut self, priority: usize, state: S) {
+        assert!(
+            priority >= self.current,
+            "priority {} is below the current {}",
+            priority,
+            self.current
+        );
+        if priority >= self.buckets.len() {
+            self.buckets.resize_with(priority + 1, Vec::new);
+        }
+        self.buckets[priority].push(state);
+    }
+
+    /// Pops the most recently pushed state with the lowest priority.
+    fn pop(&mut self) -> Option<S> {
+        while self.current < self.buckets.len() {
+            if let Some(state) = self.buckets[self.current].pop() {
+                return Some(state);
+            }
+            self.current += 1;
+        }
+        None
+    }
+}
+
+pub fn solve_fn_buckets<F, S>(next: F, states: Vec<(usize, S)>) -> S
+where
+    F: FnMut(&mut BucketQueue<S>, &S) -> NodeResult,
+{
+    try_solve_fn_buckets(next, states).expect("Stop condition not triggered")
+}
+
+/// Like [`try_solve_fn_priority`], but with a [`BucketQueue`] in which the states are pushed
+/// together with their priority, lowest first.
+pub fn try_solve_fn_buckets<F, S>(mut next: F, states: Vec<(usize, S)>) -> Option<S>
+where
+    F: FnMut(&mut BucketQueue<S>, &S) -> NodeResult,
+{
+    let mut queue = BucketQueue {
+        buckets: vec![],
+        current: 0,
+    };
+    for (priority, state) in states {
+        queue.push(priority, state);
+    }
+
+    while let Some(current) = queue.pop() {
+        match next(&mut queue, &current) {
+            NodeResult::Next => {}
+            NodeResult::Stop => return Some(current),
+        }
+    }
+    None
+}
+
+/// Where a sequence of states repeats: the state after `start + period` steps is the state after
+/// `start` steps, and `period` is the smallest such distance.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Cycle {
+    pub start: usize,
+    pub period: usize,
+}
+
+impl Cycle {
+    /// The first step whose state is the state after `n` steps, so at most `start + period - 1`.
+    pub fn equivalent_step(&self, n: usize) -> usize {
+        match n.checked_sub(self.start) {
+            None => n,
+            Some(into_cycle) => self.start + into_cycle % self.period,
+        }
+    }
+
+    /// The state after `n` steps, without stepping through more than one period.
+    pub fn state_at<S>(&self, initial: S, mut step: impl FnMut(&S) -> S, n: usize) -> S {
+        (0..self.equivalent_step(n)).fold(initial, |state, _| step(&state))
+    }
+}
+
+/// Finds the cycle of the states reached by repeatedly applying `step` to `initial`, with Brent's
+/// algorithm. Only two states are kept at a time, but `step` is called up to about three times as
+/// often as [`find_cycle_hashed`] needs. Does not return if the states never repeat.
+pub fn find_cycle_brent<S>(initial: S, mut step: impl FnMut(&S) -> S) -> Cycle
+where
+    S: Clone + PartialEq,
+{
+    // Find the period by moving the hare ahead until it meets the tortoise, which jumps to the
+    // hare at every power of two.
+    let mut power = 1;
+    let mut period = 1;
+    let mut tortoise = initial.clone();
+    let mut hare = step(&initial);
+    while tortoise != hare {
+        if power == period {
+            tortoise = hare.clone();
+            power *= 2;
+            period = 0;
+        }
+        hare = step(&hare);
+        period += 1;
+    }
+
+    // With the hare one period ahead, both meet at the start of the cycle.
+    let mut tortoise = initial.clone();
+    let mut hare = (0..period).fold(initial, |state, _| step(&state));
+    let mut start = 0;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        start += 1;
+    }
+    Cycle { start, period }
+}
+
+/// Finds the cycle of the states reached by repeatedly applying `step` to `initial`, by looking
+/// up the `fingerprint` of every state. Two states must have the same fingerprint exactly if they
+/// are equal, so it can leave out what is the same in every state or pack it more tightly.
+///
+/// Also returns the fingerprint after each step up to the end of the first period, which
+/// [`Cycle::equivalent_step`] indexes for any later step. Does not return if the states never
+/// repeat.
+pub fn find_cycle_hashed<S, K>(
+    initial: S,
+    mut step: impl FnMut(&S) -> S,
+    mut fingerprint: impl FnMut(&S) -> K,
+) -> (Cycle, Vec<K>)
+where
+    K: Hash + Eq + Clone,
+{
+    let mut seen = FxHashMap::default();
+    let mut fingerprints = vec![];
+    let mut state = initial;
+    loop {
+        let current = fingerprint(&state);
+        match seen.entry(current.clone()) {
+            Entry::Occupied(entry) => {
+                let start = *entry.get();
+                let period = fingerprints.len() - start;
+                return (Cycle { start, period }, fingerprints);
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(fingerprints.len());
+            }
+        }
+        fingerprints.push(current);
+        state = step(&state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::grid::{BackedGrid, GridView};
+    use crate::util::position::Position;
+
+    const GARDEN: &str = "...........
+.....###.#.
+.###.##..#.
+..#.#...#..
+....#.#....
+.##..S####.
+.##..#...#.
+.......##..
+.##.#.####.
+.##..##.##.
+...........";
+
+    fn garden_steps<'a>(
+        garden: &'a BackedGrid<'a, u8>,
+    ) -> impl FnMut(&Position) -> Vec<Position> + 'a {
+        |pos| {
+            garden
+                .neighbours4(pos)
+                .filter(|(_, &cell)| cell != b'#')
+                .map(|(next, _)| next)
+                .collect()
+        }
+    }
+
+    #[test]
+    fn breadth_first_layers() {
+        let garden = BackedGrid::from_data_and_row_separator(GARDEN.as_bytes(), b'\n');
+        let start = Position(5, 5);
+        let within = bfs_within([start], garden_steps(&garden), |&pos| pos, 6);
+        assert_eq!(within.iter().filter(|(_, d)| d % 2 == 0).count(), 16);
+        assert!(within.windows(2).all(|pair| pair[0].1 <= pair[1].1));
+
+        let distances = bfs_distances([start], garden_steps(&garden), |&pos| pos);
+        assert_eq!(distances[&Position(0, 0)], 10);
+        assert_eq!(distances.len(), 81);
+        let found = bfs(
+            [start],
+            garden_steps(&garden),
+            |&pos| pos,
+            |pos| pos.y() == 0,
+        );
+        assert_eq!(found.map(|(_, distance)| distance), Some(8));
+        let unreachable = bfs(
+            [start],
+            garden_steps(&garden),
+            |&pos| pos,
+            |pos| pos.x() == 20,
+        );
+        assert_eq!(unreachable, None);
+    }
+
+    /// Steps of +1 cost 1 and steps of +3 cost 2, up to 10.
+    fn successors(&n: &u32) -> impl Iterator<Item = (u32, u32)> {
+        [(n + 1, 1), (n + 3, 2)]
+            .into_iter()
+            .filter(|&(next, _)| next <= 10)
+    }
+
+    #[test]
+    fn shortest_path() {
+        let (cost, path) = dijkstra([0], successors, |&n| n == 10, |&n| n).unwrap();
+        assert_eq!(cost, 7);
+        assert_eq!(path.first(), Some(&0));
+        assert_eq!(path.last(), Some(&10));
+        let steps = path.windows(2).map(|pair| pair[1] - pair[0]);
+        assert_eq!(steps.map(|step| step.min(2)).sum::<u32>(), cost);
+
+        let distance = |&n: &u32| (10 - n).div_ceil(3) * 2;
+        let found = a_star([0], successors, |&n| n == 10, |&n| n, distance);
+        assert_eq!(found.map(|(cost, _)| cost), Some(7));
+    }
+    #[test]
+    fn several_starts_and_unreachable() {
+        assert_eq!(
+            dijkstra([4, 9], successors, |&n| n == 10, |&n| n),
+            Some((1, vec![9, 10]))
+        );
+        assert_eq!(dijkstra([0], successors, |&n| n == 11, |&n| n), None);
+        assert_eq!(dijkstra([], successors, |_| true, |&n| n), None);
+    }
+    #[test]
+    fn cycles() {
+        // 0, 1, 2, then 3, 4, 5, 6 repeating.
+        let step = |&n: &u32| if n == 6 { 3 } else { n + 1 };
+        let expected = Cycle {
+            start: 3,
+            period: 4,
+        };
+        assert_eq!(find_cycle_brent(0, step), expected);
+        let (cycle, fingerprints) = find_cycle_hashed(0, step, |&n| n);
+        assert_eq!(cycle, expected);
+        assert_eq!(fingerprints, [0, 1, 2, 3, 4, 5, 6]);
+        assert_eq!(cycle.equivalent_step(2), 2);
+        assert_eq!(cycle.equivalent_step(1_000_000_000), 4);
+        assert_eq!(fingerprints[cycle.equivalent_step(7)], 3);
+        assert_eq!(cycle.state_at(0, step, 1_000_000_001), 5);
+
+        // A cycle right from the start, found through a coarser fingerprint.
+        let step = |&(n, label): &(u32, char)| ((n + 1) % 5, label);
+        let (cycle, _) = find_cycle_hashed((0, 'a'), step, |&(n, _)| n);
+        let expected = Cycle {
+            start: 0,
+            period: 5,
+        };
+        assert_eq!(cycle, expected);
+        assert_eq!(find_cycle_brent((0, 'a'), step), expected);
+    }
+    #[test]
+    fn buckets() {
+        // The same graph as in `shortest_path`, with the cost as part of the state.
+        let next = |queue: &mut BucketQueue<(u32, u32)>, &(n, cost): &(u32, u32)| {
+            if n == 10 {
+                return NodeResult::Stop;
+            }
+            for (next, step_cost) in successors(&n) {
+                queue.push((cost + step_cost) as usize, (next, cost + step_cost));
+            }
+            NodeResult::Next
+        };
+        assert_eq!(solve_fn_buckets(next, vec![(0, (0, 0))]), (10, 7));
+        assert_eq!(try_solve_fn_buckets(next, vec![(0, (11, 0))]), None);
+    }
+    #[test]
+    #[should_panic(expected = "priority 1 is below the current 2")]
+    fn buckets_monotone() {
+        let next = |queue: &mut BucketQueue<usize>, &priority: &usize| {
+            queue.push(priority - 1, 0);
+            NodeResult::Next
+        };
+        try_solve_fn_buckets(next, vec![(2, 2)]);
+    }
 }
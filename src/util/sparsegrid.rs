@@ -0,0 +1,150 @@
+use crate::util::grid::{CellDisplay, Grid};
+use crate::util::position::{Dimensions, IVec2};
+use rustc_hash::FxHashMap;
+use std::fmt::{Display, Formatter};
+
+/// A grid over signed coordinates which only stores the cells that were set.
+///
+/// The bounding box grows to include every inserted position and does not shrink on removal.
+#[derive(Clone, Debug)]
+pub struct SparseGrid<T> {
+    cells: FxHashMap<IVec2, T>,
+    bounds: Option<(IVec2, IVec2)>,
+}
+
+impl<T> Default for SparseGrid<T> {
+    fn default() -> Self {
+        Self {
+            cells: FxHashMap::default(),
+            bounds: None,
+        }
+    }
+}
+
+impl<T> SparseGrid<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, pos: IVec2, value: T) -> Option<T> {
+        self.bounds = Some(match self.bounds {
+            None => (pos, pos),
+            Some((min, max)) => (
+                IVec2::new(min.y.min(pos.y), min.x.min(pos.x)),
+                IVec2::new(max.y.max(pos.y), max.x.max(pos.x)),
+            ),
+        });
+        self.cells.insert(pos, value)
+    }
+
+    pub fn get(&self, pos: &IVec2) -> Option<&T> {
+        self.cells.get(pos)
+    }
+
+    pub fn get_mut(&mut self, pos: &IVec2) -> Option<&mut T> {
+        self.cells.get_mut(pos)
+    }
+
+    pub fn remove(&mut self, pos: &IVec2) -> Option<T> {
+        self.cells.remove(pos)
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (IVec2, &T)> + '_ {
+        self.cells.iter().map(|(pos, value)| (*pos, value))
+    }
+
+    /// The top left and bottom right corners of the bounding box, both inclusive.
+    pub fn bounds(&self) -> Option<(IVec2, IVec2)> {
+        self.bounds
+    }
+
+    pub fn dimensions(&self) -> Dimensions {
+        self.bounds.map_or(Dimensions::default(), |(min, max)| {
+            Dimensions::from_height_width(
+                (max.y - min.y) as usize + 1,
+                (max.x - min.x) as usize + 1,
+            )
+        })
+    }
+
+    /// Copies the bounding box into a dense grid, filling unset cells with `empty`. Also returns
+    /// the coordinates of the top left cell, which the dense grid puts at the origin.
+    pub fn to_grid(&self, empty: T) -> (Grid<T>, IVec2)
+    where
+        T: Clone,
+    {
+        let origin = self.bounds.map_or(IVec2::ZERO, |(min, _)| min);
+        let mut grid = Grid::from_dimensions(self.dimensions(), empty);
+        for (pos, value) in self.iter() {
+            // Every cell lies inside the bounding box.
+            let pos = (pos - origin).to_position(&grid.dimensions).unwrap();
+            grid.set(&pos, value.clone());
+        }
+        (grid, origin)
+    }
+}
+
+impl<T> FromIterator<(IVec2, T)> for SparseGrid<T> {
+    fn from_iter<I: IntoIterator<Item = (IVec2, T)>>(iter: I) -> Self {
+        let mut grid = Self::new();
+        grid.extend(iter);
+        grid
+    }
+}
+
+impl<T> Extend<(IVec2, T)> for SparseGrid<T> {
+    fn extend<I: IntoIterator<Item = (IVec2, T)>>(&mut self, iter: I) {
+        iter.into_iter().for_each(|(pos, value)| {
+            self.insert(pos, value);
+        });
+    }
+}
+
+/// Renders the bounding box, with unset cells shown as the default value.
+impl<T> Display for SparseGrid<T>
+where
+    T: CellDisplay + Clone + Default,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.to_grid(T::default()).0.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bounds_grow() {
+        let mut grid = SparseGrid::new();
+        assert_eq!(grid.dimensions(), Dimensions::from_height_width(0, 0));
+        grid.insert(IVec2::new(0, 0), true);
+        grid.insert(IVec2::new(-2, 3), true);
+        grid.insert(IVec2::new(1, -1), true);
+        assert_eq!(grid.bounds(), Some((IVec2::new(-2, -1), IVec2::new(1, 3))));
+        assert_eq!(grid.dimensions(), Dimensions::from_height_width(4, 5));
+        assert_eq!(grid.remove(&IVec2::new(1, -1)), Some(true));
+        assert_eq!(grid.len(), 2);
+        assert_eq!(grid.dimensions(), Dimensions::from_height_width(4, 5));
+    }
+    #[test]
+    fn to_grid() {
+        let grid = [(IVec2::new(-1, -1), true), (IVec2::new(0, 1), true)]
+            .into_iter()
+            .collect::<SparseGrid<_>>();
+        let (dense, origin) = grid.to_grid(false);
+        assert_eq!(origin, IVec2::new(-1, -1));
+        assert_eq!(dense.dimensions, Dimensions::from_height_width(2, 3));
+        assert_eq!(dense.count(), 2);
+        assert_eq!(grid.to_string(), "█  \n  █\n");
+        assert_eq!(SparseGrid::<bool>::new().to_string(), "");
+    }
+}